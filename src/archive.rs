@@ -0,0 +1,161 @@
+use chrono::{Local, NaiveDate, TimeZone};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::decompress::{self, Compression};
+
+// Archives `--search-archives` opens like directories
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    // Also jar, war and ear, which are zip files
+    Zip,
+    // A tarball, possibly compressed as a whole
+    Tar(Option<Compression>),
+}
+
+impl ArchiveFormat {
+    pub fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        let (_, extension) = name.rsplit_once('.')?;
+        match extension {
+            "zip" | "jar" | "war" | "ear" => Some(ArchiveFormat::Zip),
+            "tar" => Some(ArchiveFormat::Tar(None)),
+            "tgz" => Some(ArchiveFormat::Tar(Some(Compression::Gzip))),
+            "tbz2" => Some(ArchiveFormat::Tar(Some(Compression::Bzip2))),
+            "txz" => Some(ArchiveFormat::Tar(Some(Compression::Xz))),
+            _ if name
+                .strip_suffix(extension)
+                .is_some_and(|stem| stem.ends_with(".tar.")) =>
+            {
+                Compression::of(path).map(|c| ArchiveFormat::Tar(Some(c)))
+            }
+            _ => None,
+        }
+    }
+}
+
+// A file or directory inside an archive
+#[derive(Debug, Clone)]
+pub struct Member {
+    // `bundle.zip!/src/main.rs`
+    pub path: PathBuf,
+    pub is_dir: bool,
+    // Uncompressed size
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+// The path shown for a member: the archive path, `!/`, then the name inside
+pub fn member_path(archive: &Path, name: &str) -> PathBuf {
+    let mut path = OsString::from(archive.as_os_str());
+    path.push("!/");
+    path.push(name.trim_end_matches('/'));
+    PathBuf::from(path)
+}
+
+// Every member of an archive, in the order it stores them
+pub(crate) fn members(path: &Path, format: ArchiveFormat) -> io::Result<Vec<Member>> {
+    let file = File::open(path)?;
+    let mut members = Vec::new();
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = zip::ZipArchive::new(file)?;
+            for index in 0..zip.len() {
+                // Only reads the central directory, not the contents
+                let entry = zip.by_index_raw(index)?;
+                members.push(Member {
+                    path: member_path(path, entry.name()),
+                    is_dir: entry.is_dir(),
+                    size: entry.size(),
+                    modified: entry.last_modified().and_then(zip_time),
+                });
+            }
+        }
+        ArchiveFormat::Tar(compression) => {
+            let mut tar = tar::Archive::new(tar_reader(file, compression)?);
+            for entry in tar.entries()? {
+                let entry = entry?;
+                let header = entry.header();
+                let kind = header.entry_type();
+                if !kind.is_file() && !kind.is_dir() {
+                    continue;
+                }
+                members.push(Member {
+                    path: member_path(path, &entry.path()?.to_string_lossy()),
+                    is_dir: kind.is_dir(),
+                    size: header.size()?,
+                    modified: header
+                        .mtime()
+                        .ok()
+                        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+                });
+            }
+        }
+    }
+    Ok(members)
+}
+
+// Read each regular file in an archive in turn. A tarball is read in one
+// pass, so its members can't be revisited. An error names the member it
+// happened in.
+pub(crate) fn read_files(
+    path: &Path,
+    format: ArchiveFormat,
+    mut visit: impl FnMut(&Path, &mut dyn Read) -> io::Result<()>,
+) -> io::Result<()> {
+    let file = File::open(path)?;
+    let in_member = |member: &Path, e: io::Error| {
+        let name = member.strip_prefix(path).unwrap_or(member);
+        io::Error::new(e.kind(), format!("{}: {}", name.display(), e))
+    };
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = zip::ZipArchive::new(file)?;
+            for index in 0..zip.len() {
+                let mut entry = zip.by_index(index)?;
+                if !entry.is_file() {
+                    continue;
+                }
+                let member = member_path(path, entry.name());
+                visit(&member, &mut entry).map_err(|e| in_member(&member, e))?;
+            }
+        }
+        ArchiveFormat::Tar(compression) => {
+            let mut tar = tar::Archive::new(tar_reader(file, compression)?);
+            for entry in tar.entries()? {
+                let mut entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let member = member_path(path, &entry.path()?.to_string_lossy());
+                visit(&member, &mut entry).map_err(|e| in_member(&member, e))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn tar_reader(file: File, compression: Option<Compression>) -> io::Result<Box<dyn Read>> {
+    match compression {
+        Some(compression) => decompress::reader(file, compression),
+        None => Ok(Box::new(file)),
+    }
+}
+
+// Zip timestamps are local time with two-second resolution
+fn zip_time(time: zip::DateTime) -> Option<SystemTime> {
+    let datetime =
+        NaiveDate::from_ymd_opt(time.year().into(), time.month().into(), time.day().into())?
+            .and_hms_opt(
+                time.hour().into(),
+                time.minute().into(),
+                time.second().into(),
+            )?;
+    Local
+        .from_local_datetime(&datetime)
+        .earliest()
+        .map(SystemTime::from)
+}
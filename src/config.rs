@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+// Settings shared by `file_finder` and `ffinder`, stored at
+// `$XDG_CONFIG_HOME/ffinder/config.toml`. Command-line flags take precedence.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub color_theme: ColorTheme,
+    // Worker threads for grep; unset means one per CPU
+    pub threads: Option<usize>,
+    // Directories and globs to exclude; unset means the built-in list in ffinder
+    pub excludes: Option<Vec<String>>,
+    pub progress: ProgressLevel,
+    pub defaults: DefaultFlags,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorTheme {
+    // Colors only when writing to a terminal
+    #[default]
+    Auto,
+    Colorful,
+    Monochrome,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressLevel {
+    // No spinner, for callers that own the terminal
+    Off,
+    // Spinner only
+    Minimal,
+    // Spinner with a running file count
+    #[default]
+    Standard,
+    // File count plus the file being searched
+    Detailed,
+    // Detailed, plus timing and throughput after the search
+    Verbose,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DefaultFlags {
+    pub ignore_case: bool,
+    pub regex: bool,
+    pub hidden: bool,
+    pub no_ignore: bool,
+    pub follow: bool,
+    pub extensions: Option<String>,
+    pub context: Option<usize>,
+}
+
+// Flags from the command line: `Some(false)` for a negation such as
+// --no-regex, `None` where neither form was given
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlagOverrides {
+    pub ignore_case: Option<bool>,
+    pub regex: Option<bool>,
+    pub hidden: Option<bool>,
+    pub no_ignore: Option<bool>,
+    pub follow: Option<bool>,
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(dirs::config_dir)?;
+        Some(base.join("ffinder").join("config.toml"))
+    }
+
+    // A missing file gives the defaults; a broken one is reported and ignored
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("Ignoring invalid config {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<PathBuf> {
+        let path = Self::path().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "no configuration directory")
+        })?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&path, contents)?;
+        Ok(path)
+    }
+}
+
+impl DefaultFlags {
+    // The saved flags with the command-line choices applied, either way
+    pub fn merge(&self, cli: FlagOverrides) -> DefaultFlags {
+        DefaultFlags {
+            ignore_case: cli.ignore_case.unwrap_or(self.ignore_case),
+            regex: cli.regex.unwrap_or(self.regex),
+            hidden: cli.hidden.unwrap_or(self.hidden),
+            no_ignore: cli.no_ignore.unwrap_or(self.no_ignore),
+            follow: cli.follow.unwrap_or(self.follow),
+            ..self.clone()
+        }
+    }
+}
+
+impl ColorTheme {
+    pub fn apply(self) {
+        match self {
+            ColorTheme::Auto => {
+                if !io::stdout().is_terminal() {
+                    colored::control::set_override(false);
+                }
+            }
+            ColorTheme::Colorful => colored::control::set_override(true),
+            ColorTheme::Monochrome => colored::control::set_override(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_round_trip() {
+        let config = Config {
+            color_theme: ColorTheme::Monochrome,
+            threads: Some(4),
+            excludes: Some(vec!["target".to_string(), "*.log".to_string()]),
+            progress: ProgressLevel::Detailed,
+            defaults: DefaultFlags {
+                ignore_case: true,
+                ..DefaultFlags::default()
+            },
+        };
+        let text = toml::to_string_pretty(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&text).unwrap(), config);
+    }
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let config: Config = toml::from_str("threads = 2\n[defaults]\nregex = true\n").unwrap();
+        assert_eq!(config.threads, Some(2));
+        assert_eq!(config.progress, ProgressLevel::Standard);
+        assert!(config.defaults.regex);
+        assert!(!config.defaults.ignore_case);
+    }
+
+    #[test]
+    fn test_command_line_flags_win() {
+        let saved = DefaultFlags {
+            ignore_case: true,
+            regex: true,
+            follow: true,
+            context: Some(2),
+            ..DefaultFlags::default()
+        };
+        let cli = FlagOverrides {
+            ignore_case: Some(false),
+            hidden: Some(true),
+            ..FlagOverrides::default()
+        };
+        let flags = saved.merge(cli);
+        assert!(!flags.ignore_case);
+        assert!(flags.hidden);
+        assert!(flags.regex);
+        assert!(flags.follow);
+        assert!(!flags.no_ignore);
+        assert_eq!(flags.context, Some(2));
+        assert_eq!(saved.merge(FlagOverrides::default()), saved);
+    }
+}
@@ -0,0 +1,33 @@
+use encoding_rs::Encoding;
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use std::io::{BufRead, BufReader, Read};
+
+// Read a file, or decompressed file, for grep as a stream of UTF-8. A UTF-8
+// or UTF-16 byte order mark picks the encoding, then `encoding` if given;
+// otherwise the bytes are searched as they are, and invalid UTF-8 is only
+// replaced for display.
+pub(crate) fn reader(source: impl Read, encoding: Option<&'static Encoding>) -> impl BufRead {
+    let decoder = DecodeReaderBytesBuilder::new()
+        .encoding(encoding)
+        .utf8_passthru(true)
+        .strip_bom(true)
+        .bom_override(true)
+        .build(source);
+    BufReader::new(decoder)
+}
+
+// An encoding by its WHATWG label: `latin1`, `shift_jis`, `utf-16le`, `gbk`...
+pub fn parse_encoding(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| {
+        format!(
+            "unknown encoding '{}', expected e.g. latin1, shift_jis or utf-16le",
+            label
+        )
+    })
+}
+
+// UTF-16 text is mostly NUL bytes when it's ASCII, so it mustn't be mistaken
+// for binary
+pub(crate) fn has_utf16_bom(bytes: &[u8]) -> bool {
+    bytes.starts_with(&[0xFF, 0xFE]) || bytes.starts_with(&[0xFE, 0xFF])
+}
@@ -0,0 +1,39 @@
+use std::io::{self, Read};
+use std::path::Path;
+
+// Compressed formats grep can read through with `--search-zip`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    // Picked by extension, so `app.log.3.gz` is gzip whatever it contains
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gz" => Some(Compression::Gzip),
+            "bz2" => Some(Compression::Bzip2),
+            "xz" => Some(Compression::Xz),
+            "zst" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+}
+
+// Decompress `source` as a stream. Concatenated members, as left by
+// `cat a.gz b.gz` or parallel compressors, are read one after another.
+pub(crate) fn reader<'a>(
+    source: impl Read + 'a,
+    compression: Compression,
+) -> io::Result<Box<dyn Read + 'a>> {
+    Ok(match compression {
+        Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(source)),
+        Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(source)),
+        Compression::Xz => Box::new(liblzma::read::XzDecoder::new_multi_decoder(source)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(source)?),
+    })
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum SearchError {
+    InvalidPattern(regex::Error),
+    InvalidExclude(globset::Error),
+    InvalidGlob(globset::Error),
+    PatternSet(aho_corasick::BuildError),
+    ThreadPool(rayon::ThreadPoolBuildError),
+}
+
+// A path that couldn't be walked or read. Unlike `SearchError` this doesn't
+// stop the search; these are collected and listed after the results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::InvalidPattern(e) => write!(f, "Invalid regex pattern: {}", e),
+            SearchError::InvalidExclude(e) => write!(f, "Invalid exclude pattern: {}", e),
+            SearchError::InvalidGlob(e) => write!(f, "Invalid glob pattern: {}", e),
+            SearchError::PatternSet(e) => write!(f, "Too many patterns: {}", e),
+            SearchError::ThreadPool(e) => write!(f, "Failed to start worker threads: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SearchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SearchError::InvalidPattern(e) => Some(e),
+            SearchError::InvalidExclude(e) => Some(e),
+            SearchError::InvalidGlob(e) => Some(e),
+            SearchError::PatternSet(e) => Some(e),
+            SearchError::ThreadPool(e) => Some(e),
+        }
+    }
+}
+
+impl From<regex::Error> for SearchError {
+    fn from(e: regex::Error) -> Self {
+        SearchError::InvalidPattern(e)
+    }
+}
+
+impl From<globset::Error> for SearchError {
+    fn from(e: globset::Error) -> Self {
+        SearchError::InvalidExclude(e)
+    }
+}
+
+impl From<aho_corasick::BuildError> for SearchError {
+    fn from(e: aho_corasick::BuildError) -> Self {
+        SearchError::PatternSet(e)
+    }
+}
+
+impl From<rayon::ThreadPoolBuildError> for SearchError {
+    fn from(e: rayon::ThreadPoolBuildError) -> Self {
+        SearchError::ThreadPool(e)
+    }
+}
@@ -0,0 +1,515 @@
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::Once;
+
+// Turns documents grep can't read as text into text it can search. Register
+// one with `GrepSearcher::with_extractor`.
+pub trait Extractor: Send + Sync {
+    // Whether this extractor reads `path`, judged by its name
+    fn handles(&self, path: &Path) -> bool;
+
+    // The document's text in reading order, split wherever the location
+    // reported with a match changes
+    fn extract(&self, path: &Path) -> io::Result<Vec<Segment>>;
+}
+
+// A stretch of extracted text, such as a page or a spreadsheet cell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    // Shown with matches: `page 3`, `Sheet1!B4`, `slide 2`
+    pub location: Option<String>,
+    pub text: String,
+}
+
+impl Segment {
+    fn new(location: Option<String>, text: String) -> Self {
+        Segment { location, text }
+    }
+}
+
+// Text from PDF content streams, one segment per page
+pub struct PdfExtractor;
+
+impl Extractor for PdfExtractor {
+    fn handles(&self, path: &Path) -> bool {
+        has_extension(path, &["pdf"])
+    }
+
+    fn extract(&self, path: &Path) -> io::Result<Vec<Segment>> {
+        // The PDF parser panics on some malformed files
+        let pages = catch_quietly(|| pdf_extract::extract_text_by_pages(path))
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "unreadable PDF"))?
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+        Ok(pages
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| Segment::new(Some(format!("page {}", i + 1)), text))
+            .collect())
+    }
+}
+
+// Word, Excel and PowerPoint files, which are zip archives of XML parts.
+// Paragraphs of a document, cells of a workbook and paragraphs of a slide
+// each become a line.
+pub struct OfficeExtractor;
+
+impl Extractor for OfficeExtractor {
+    fn handles(&self, path: &Path) -> bool {
+        has_extension(path, &["docx", "xlsx", "pptx"])
+    }
+
+    fn extract(&self, path: &Path) -> io::Result<Vec<Segment>> {
+        let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+        let extension = path.extension().unwrap_or_default().to_ascii_lowercase();
+        match extension.to_str() {
+            Some("docx") => {
+                let document = read_part(&mut zip, "word/document.xml")?;
+                Ok(vec![Segment::new(None, paragraphs(&document, b"p", b"t")?)])
+            }
+            Some("xlsx") => workbook_cells(&mut zip),
+            _ => slides(&mut zip),
+        }
+    }
+}
+
+thread_local! {
+    static QUIET_PANICS: Cell<bool> = const { Cell::new(false) };
+}
+
+// Catch a panic in `f` without the panic hook printing it, since it's
+// reported as an error instead. The hook is wrapped once per process, and
+// other threads' panics still go through it.
+fn catch_quietly<T>(f: impl FnOnce() -> T) -> std::thread::Result<T> {
+    static WRAP_HOOK: Once = Once::new();
+    WRAP_HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET_PANICS.with(Cell::get) {
+                hook(info);
+            }
+        }));
+    });
+    QUIET_PANICS.with(|quiet| quiet.set(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    QUIET_PANICS.with(|quiet| quiet.set(false));
+    result
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.iter().any(|x| e.eq_ignore_ascii_case(x)))
+}
+
+type Package = zip::ZipArchive<File>;
+
+fn read_part(zip: &mut Package, name: &str) -> io::Result<String> {
+    let mut part = zip.by_name(name)?;
+    let mut xml = String::new();
+    part.read_to_string(&mut xml)?;
+    Ok(xml)
+}
+
+// What `walk_xml` reports, with namespace prefixes dropped from names
+enum Node<'a> {
+    Open(&'a BytesStart<'a>),
+    Close(&'a [u8]),
+    Text(&'a str),
+}
+
+fn walk_xml(xml: &str, mut visit: impl FnMut(Node)) -> io::Result<()> {
+    let invalid =
+        |e: &dyn std::fmt::Display| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event().map_err(|e| invalid(&e))? {
+            Event::Start(element) => visit(Node::Open(&element)),
+            Event::Empty(element) => {
+                visit(Node::Open(&element));
+                visit(Node::Close(element.local_name().as_ref()));
+            }
+            Event::End(element) => visit(Node::Close(element.local_name().as_ref())),
+            Event::Text(text) => visit(Node::Text(&text.xml_content().map_err(|e| invalid(&e))?)),
+            Event::CData(text) => visit(Node::Text(&text.decode().map_err(|e| invalid(&e))?)),
+            Event::GeneralRef(entity) => {
+                let name = entity.decode().map_err(|e| invalid(&e))?;
+                if let Some(c) = entity.resolve_char_ref().map_err(|e| invalid(&e))? {
+                    visit(Node::Text(c.encode_utf8(&mut [0; 4])));
+                } else if let Some(text) = resolve_predefined_entity(&name) {
+                    visit(Node::Text(text));
+                }
+            }
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    let value = element.try_get_attribute(name).ok()??;
+    value.unescape_value().ok().map(|v| v.into_owned())
+}
+
+// The text of each `paragraph` element on its own line, taken from the
+// `text` elements inside it
+fn paragraphs(xml: &str, paragraph: &[u8], text: &[u8]) -> io::Result<String> {
+    let mut out = String::new();
+    let mut in_text = false;
+    walk_xml(xml, |node| match node {
+        Node::Open(element) => match element.local_name().as_ref() {
+            name if name == text => in_text = true,
+            b"tab" => out.push('\t'),
+            b"br" => out.push(' '),
+            _ => {}
+        },
+        Node::Close(name) if name == text => in_text = false,
+        Node::Close(name) if name == paragraph => out.push('\n'),
+        Node::Text(content) if in_text => out.push_str(content),
+        _ => {}
+    })?;
+    Ok(out)
+}
+
+// `ppt/slides/slide3.xml` is slide 3
+fn slides(zip: &mut Package) -> io::Result<Vec<Segment>> {
+    let mut numbered: Vec<(usize, String)> = zip
+        .file_names()
+        .filter_map(|name| {
+            let number = name
+                .strip_prefix("ppt/slides/slide")?
+                .strip_suffix(".xml")?
+                .parse()
+                .ok()?;
+            Some((number, name.to_string()))
+        })
+        .collect();
+    numbered.sort();
+
+    let mut segments = Vec::new();
+    for (number, name) in numbered {
+        let text = paragraphs(&read_part(zip, &name)?, b"p", b"t")?;
+        segments.push(Segment::new(Some(format!("slide {}", number)), text));
+    }
+    Ok(segments)
+}
+
+// One segment per non-empty cell, located as `Sheet!A1`
+fn workbook_cells(zip: &mut Package) -> io::Result<Vec<Segment>> {
+    let shared = match read_part(zip, "xl/sharedStrings.xml") {
+        Ok(xml) => shared_strings(&xml)?,
+        Err(_) => Vec::new(),
+    };
+
+    // Sheet names in workbook order, and the parts they live in
+    let mut sheets = Vec::new();
+    walk_xml(&read_part(zip, "xl/workbook.xml")?, |node| {
+        if let Node::Open(element) = node {
+            if element.local_name().as_ref() == b"sheet" {
+                if let (Some(name), Some(id)) =
+                    (attribute(element, "name"), attribute(element, "r:id"))
+                {
+                    sheets.push((name, id));
+                }
+            }
+        }
+    })?;
+    let mut targets = HashMap::new();
+    walk_xml(&read_part(zip, "xl/_rels/workbook.xml.rels")?, |node| {
+        if let Node::Open(element) = node {
+            if let (Some(id), Some(target)) =
+                (attribute(element, "Id"), attribute(element, "Target"))
+            {
+                targets.insert(id, target);
+            }
+        }
+    })?;
+
+    let mut segments = Vec::new();
+    for (name, id) in sheets {
+        let Some(target) = targets.get(&id) else {
+            continue;
+        };
+        let part = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{}", target),
+        };
+        let xml = read_part(zip, &part)?;
+        for (cell, text) in sheet_cells(&xml, &shared)? {
+            segments.push(Segment::new(Some(format!("{}!{}", name, cell)), text));
+        }
+    }
+    Ok(segments)
+}
+
+fn shared_strings(xml: &str) -> io::Result<Vec<String>> {
+    let mut strings = Vec::new();
+    let (mut in_text, mut in_phonetic) = (false, false);
+    walk_xml(xml, |node| match node {
+        Node::Open(element) => match element.local_name().as_ref() {
+            b"si" => strings.push(String::new()),
+            b"t" => in_text = true,
+            // Furigana runs repeat the text they annotate
+            b"rPh" => in_phonetic = true,
+            _ => {}
+        },
+        Node::Close(b"t") => in_text = false,
+        Node::Close(b"rPh") => in_phonetic = false,
+        Node::Text(text) if in_text && !in_phonetic => {
+            if let Some(last) = strings.last_mut() {
+                last.push_str(text);
+            }
+        }
+        _ => {}
+    })?;
+    Ok(strings)
+}
+
+// Each cell's reference and displayed value; formulas are left out
+fn sheet_cells(xml: &str, shared: &[String]) -> io::Result<Vec<(String, String)>> {
+    let mut cells = Vec::new();
+    let mut cell: Option<(String, Option<String>)> = None;
+    let mut value = String::new();
+    let mut in_value = false;
+    walk_xml(xml, |node| match node {
+        Node::Open(element) => match element.local_name().as_ref() {
+            b"c" => {
+                let reference = attribute(element, "r").unwrap_or_default();
+                cell = Some((reference, attribute(element, "t")));
+                value.clear();
+            }
+            b"v" | b"t" => in_value = true,
+            _ => {}
+        },
+        Node::Close(b"v" | b"t") => in_value = false,
+        Node::Close(b"c") => {
+            if let Some((reference, kind)) = cell.take() {
+                let text = match kind.as_deref() {
+                    Some("s") => value
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| shared.get(i).cloned())
+                        .unwrap_or_default(),
+                    Some("b") => if value == "1" { "TRUE" } else { "FALSE" }.to_string(),
+                    _ => std::mem::take(&mut value),
+                };
+                if !text.is_empty() {
+                    cells.push((reference, text));
+                }
+            }
+        }
+        Node::Text(text) if in_value && cell.is_some() => value.push_str(text),
+        _ => {}
+    })?;
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+
+    fn package(path: &Path, parts: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, xml) in parts {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_office_documents() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let docx = dir.path().join("spec.docx");
+        package(
+            &docx,
+            &[(
+                "word/document.xml",
+                r#"<w:document xmlns:w="w"><w:body>
+                <w:p><w:r><w:t>Scope</w:t></w:r></w:p>
+                <w:p><w:r><w:t xml:space="preserve">Tom &amp; </w:t></w:r><w:r><w:t>Jerry</w:t></w:r></w:p>
+                </w:body></w:document>"#,
+            )],
+        );
+        let text = OfficeExtractor.extract(&docx).unwrap();
+        assert_eq!(
+            text,
+            vec![Segment::new(None, "Scope\nTom & Jerry\n".into())]
+        );
+
+        let xlsx = dir.path().join("report.xlsx");
+        package(
+            &xlsx,
+            &[
+                (
+                    "xl/workbook.xml",
+                    r#"<workbook xmlns:r="r"><sheets>
+                    <sheet name="Results" sheetId="1" r:id="rId1"/>
+                    </sheets></workbook>"#,
+                ),
+                (
+                    "xl/_rels/workbook.xml.rels",
+                    r#"<Relationships>
+                    <Relationship Id="rId1" Target="worksheets/sheet1.xml"/>
+                    </Relationships>"#,
+                ),
+                (
+                    "xl/sharedStrings.xml",
+                    r#"<sst><si><t>passed</t></si><si><r><t>fai</t></r><r><t>led</t></r></si></sst>"#,
+                ),
+                (
+                    "xl/worksheets/sheet1.xml",
+                    r#"<worksheet><sheetData>
+                    <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1"><v>42</v></c></row>
+                    <row r="2"><c r="A2" t="s"><v>1</v></c><c r="B2" t="inlineStr"><is><t>flaky</t></is></c>
+                    <c r="C2"><f>SUM(B1)</f><v>42</v></c></row>
+                    </sheetData></worksheet>"#,
+                ),
+            ],
+        );
+        let cells: Vec<_> = OfficeExtractor
+            .extract(&xlsx)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.location.unwrap(), s.text))
+            .collect();
+        let expected = [
+            ("Results!A1", "passed"),
+            ("Results!B1", "42"),
+            ("Results!A2", "failed"),
+            ("Results!B2", "flaky"),
+            ("Results!C2", "42"),
+        ];
+        assert_eq!(
+            cells,
+            expected.map(|(cell, text)| (cell.to_string(), text.to_string()))
+        );
+
+        let pptx = dir.path().join("deck.pptx");
+        let slide =
+            |text: &str| format!("<p:sld><a:p><a:r><a:t>{}</a:t></a:r></a:p></p:sld>", text);
+        package(
+            &pptx,
+            &[
+                ("ppt/slides/slide10.xml", &slide("Questions")),
+                ("ppt/slides/slide2.xml", &slide("Agenda")),
+            ],
+        );
+        let slides = OfficeExtractor.extract(&pptx).unwrap();
+        assert_eq!(
+            slides,
+            vec![
+                Segment::new(Some("slide 2".into()), "Agenda\n".into()),
+                Segment::new(Some("slide 10".into()), "Questions\n".into()),
+            ]
+        );
+    }
+
+    // A PDF with these objects, the first being the catalog
+    fn write_pdf(path: &Path, objects: &[String]) {
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf += &format!("{} 0 obj\n{}\nendobj\n", i + 1, object);
+        }
+        let xref = pdf.len();
+        pdf += &format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            pdf += &format!("{:010} 00000 n \n", offset);
+        }
+        pdf += &format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        );
+        fs::write(path, pdf).unwrap();
+    }
+
+    #[test]
+    fn test_pdf_pages() {
+        // A minimal two-page PDF with one line of text per page
+        let pages = ["Introduction", "The needle is here"];
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R 5 0 R] /Count 2 >>".to_string(),
+        ];
+        for (i, text) in pages.iter().enumerate() {
+            let content = format!("BT /F1 12 Tf 72 720 Td ({}) Tj ET", text);
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents {} 0 R \
+                 /Resources << /Font << /F1 7 0 R >> >> >>",
+                4 + 2 * i
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}\nendstream",
+                content.len(),
+                content
+            ));
+        }
+        objects.push("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string());
+
+        let dir = tempfile::tempdir().unwrap();
+        write_pdf(&dir.path().join("spec.pdf"), &objects);
+        let report = crate::GrepSearcher::new(dir.path().to_str().unwrap(), false, false, None)
+            .with_extractor(PdfExtractor)
+            .search("needle")
+            .unwrap();
+        assert_eq!(report.matches.len(), 1);
+        assert_eq!(report.matches[0].location.as_deref(), Some("page 2"));
+        assert_eq!(report.matches[0].line_content, "The needle is here");
+    }
+
+    // The panic hook writes to the real stderr, so a child run of this test
+    // does the search and this one checks what it printed
+    #[test]
+    fn test_pdf_panic_is_quiet() {
+        let test = "extract::tests::test_pdf_panic_is_quiet";
+        if let Some(dir) = std::env::var_os("FF_PANICKING_PDF_DIR") {
+            let report = crate::GrepSearcher::new(dir.to_str().unwrap(), false, false, None)
+                .with_extractor(PdfExtractor)
+                .search("x")
+                .unwrap();
+            for error in report.errors {
+                eprintln!("{}", error.message);
+            }
+            return;
+        }
+
+        // The parser panics on a page without resources
+        let content = "BT /F1 12 Tf (x) Tj ET";
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R >>".to_string(),
+            format!(
+                "<< /Length {} >>\nstream\n{}\nendstream",
+                content.len(),
+                content
+            ),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+        write_pdf(&dir.path().join("broken.pdf"), &objects);
+
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([test, "--exact", "--nocapture"])
+            .env("FF_PANICKING_PDF_DIR", dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(stderr.lines().collect::<Vec<_>>(), vec!["unreadable PDF"]);
+    }
+}
@@ -1,12 +1,14 @@
 use colored::*;
 use console::Term;
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, MultiSelect, Select};
-use std::process::Command;
-
-// Global variable to store current session exclude list
-static mut CURRENT_EXCLUDES: Option<Vec<String>> = None;
+use file_finder::{
+    print_errors, ColorTheme, Config, EntryType, ErrorDisplay, FileFilters, FileRenderer,
+    FileSearcher, GrepRenderer, GrepSearcher, LiveSearch, ProgressLevel, WalkOptions,
+};
 
 fn main() {
+    Config::load().color_theme.apply();
+
     let term = Term::stdout();
     term.clear_screen().unwrap();
 
@@ -42,9 +44,10 @@ fn show_banner() {
 }
 
 fn show_main_menu() {
-    let options = vec![
+    let options = [
         "Grep Search - Find text patterns in files",
         "File Search - Find files by name",
+        "Live Search - Full-screen search as you type",
         "Configure Default Settings",
         "Help & Examples",
         "Exit",
@@ -64,9 +67,10 @@ fn show_main_menu() {
     match selection {
         Ok(0) => grep_search_wizard(),
         Ok(1) => file_search_wizard(),
-        Ok(2) => configure_settings(),
-        Ok(3) => show_help(),
-        Ok(4) => {
+        Ok(2) => live_search(),
+        Ok(3) => configure_settings(),
+        Ok(4) => show_help(),
+        Ok(5) => {
             println!();
             println!("{}", "    ╔══════════════════════════════════════════════════════════╗".bright_blue());
             println!("{}", "    ║     Thanks for using FFinder! Stay blazing fast!       ║".bright_yellow().bold());
@@ -96,6 +100,8 @@ fn grep_search_wizard() {
     println!("{}", "\nGREP SEARCH WIZARD".bright_cyan().bold());
     println!("{}", "═".repeat(50).bright_blue());
 
+    let config = Config::load();
+
     let pattern: String = Input::with_theme(&ColorfulTheme::default())
         .with_prompt("Enter search pattern")
         .interact_text()
@@ -109,37 +115,39 @@ fn grep_search_wizard() {
 
     let case_insensitive = Confirm::with_theme(&ColorfulTheme::default())
         .with_prompt("Case insensitive search?")
-        .default(false)
+        .default(config.defaults.ignore_case)
         .interact()
         .unwrap();
 
     let use_regex = Confirm::with_theme(&ColorfulTheme::default())
         .with_prompt("Use regex pattern?")
-        .default(false)
+        .default(config.defaults.regex)
         .interact()
         .unwrap();
 
     let use_progress = Confirm::with_theme(&ColorfulTheme::default())
         .with_prompt("Show detailed progress with file names?")
-        .default(true)
+        .default(config.progress != ProgressLevel::Minimal)
         .interact()
         .unwrap();
 
     let extensions: String = Input::with_theme(&ColorfulTheme::default())
         .with_prompt("File extensions (e.g., rs,py,js) or press Enter for all")
+        .with_initial_text(config.defaults.extensions.clone().unwrap_or_default())
         .allow_empty(true)
         .interact_text()
         .unwrap();
 
     let thread_count: String = Input::with_theme(&ColorfulTheme::default())
         .with_prompt("Number of threads (1-16, or press Enter for auto)")
+        .with_initial_text(config.threads.map(|t| t.to_string()).unwrap_or_default())
         .allow_empty(true)
         .interact_text()
         .unwrap();
 
     let exclude_common = Confirm::with_theme(&ColorfulTheme::default())
         .with_prompt("Exclude common directories (node_modules, target, build, etc.)?")
-        .default(false)
+        .default(config.excludes.is_some())
         .interact()
         .unwrap();
 
@@ -149,12 +157,19 @@ fn grep_search_wizard() {
         .interact_text()
         .unwrap();
 
+    let progress = match (use_progress, config.progress) {
+        (true, ProgressLevel::Verbose) => ProgressLevel::Verbose,
+        (true, _) => ProgressLevel::Detailed,
+        (false, ProgressLevel::Minimal) => ProgressLevel::Minimal,
+        (false, _) => ProgressLevel::Standard,
+    };
+
     execute_grep_search(
         &pattern,
         &directory,
         case_insensitive,
         use_regex,
-        use_progress,
+        progress,
         if extensions.is_empty() {
             None
         } else {
@@ -181,6 +196,8 @@ fn file_search_wizard() {
     println!("{}", "\nFILE SEARCH WIZARD".bright_cyan().bold());
     println!("{}", "═".repeat(50).bright_blue());
 
+    let config = Config::load();
+
     let filename: String = Input::with_theme(&ColorfulTheme::default())
         .with_prompt("Enter filename pattern")
         .interact_text()
@@ -194,17 +211,68 @@ fn file_search_wizard() {
 
     let case_insensitive = Confirm::with_theme(&ColorfulTheme::default())
         .with_prompt("Case insensitive search?")
-        .default(false)
+        .default(config.defaults.ignore_case)
         .interact()
         .unwrap();
 
     let use_regex = Confirm::with_theme(&ColorfulTheme::default())
         .with_prompt("Use regex pattern?")
-        .default(false)
+        .default(config.defaults.regex)
         .interact()
         .unwrap();
 
-    execute_file_search(&filename, &directory, case_insensitive, use_regex);
+    let kinds = ["Files", "Directories", "Anything (files, directories, symlinks)"];
+    let entry_types = [EntryType::File, EntryType::Directory, EntryType::Any];
+    let kind = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("What are you looking for?")
+        .default(0)
+        .items(&kinds[..])
+        .interact()
+        .unwrap();
+
+    execute_file_search(
+        &filename,
+        &directory,
+        case_insensitive,
+        use_regex,
+        entry_types[kind],
+    );
+}
+
+fn live_search() {
+    let config = Config::load();
+    let excludes = config
+        .excludes
+        .clone()
+        .unwrap_or_else(get_default_exclude_directories)
+        .join(",");
+    let mut live = LiveSearch::new(
+        ".",
+        config.defaults.ignore_case,
+        config.defaults.regex,
+        config.defaults.extensions.as_ref(),
+    )
+    .with_excludes(Some(&excludes))
+    .with_walk_options(walk_options(&config));
+    if let Some(threads) = config.threads {
+        live = live.with_threads(threads);
+    }
+
+    match live.run() {
+        Ok(Some(selection)) => {
+            let location = match selection.line_number {
+                Some(line) => format!("{}:{}", selection.path.display(), line),
+                None => selection.path.display().to_string(),
+            };
+            println!("{} {}", "Selected:".bright_green(), location.bright_white());
+        }
+        Ok(None) => {}
+        Err(e) => println!("{}", format!("\nLive search failed: {}", e).bright_red()),
+    }
+
+    println!("\nPress Enter to return to main menu...");
+    std::io::stdin().read_line(&mut String::new()).unwrap();
+    show_main_menu();
 }
 
 fn configure_settings() {
@@ -214,11 +282,12 @@ fn configure_settings() {
     println!("{}", "\nCONFIGURATION SETTINGS".bright_magenta().bold());
     println!("{}", "═".repeat(50).bright_blue());
 
-    let settings = vec![
+    let settings = [
         "Output Color Theme",
         "Default Thread Count",
         "Default Exclude Directories",
         "Progress Display Options",
+        "Default Search Flags",
         "Back to Main Menu",
     ];
 
@@ -234,23 +303,71 @@ fn configure_settings() {
         1 => configure_threads(),
         2 => configure_excludes(),
         3 => configure_progress(),
-        4 => show_main_menu(),
+        4 => configure_default_flags(),
+        5 => show_main_menu(),
         _ => unreachable!(),
     }
 }
 
 fn configure_colors() {
-    println!("{}", "\nColor themes coming soon!".bright_yellow());
+    println!("{}", "\nOUTPUT COLOR THEME".bright_magenta().bold());
+
+    let mut config = Config::load();
+    let themes = [
+        (ColorTheme::Auto, "Auto - Colors when writing to a terminal"),
+        (ColorTheme::Colorful, "Colorful - Always use colors"),
+        (ColorTheme::Monochrome, "Monochrome - Plain text, no colors"),
+    ];
+    let labels: Vec<&str> = themes.iter().map(|(_, label)| *label).collect();
+    let current = themes
+        .iter()
+        .position(|(theme, _)| *theme == config.color_theme)
+        .unwrap_or(0);
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Choose a color theme")
+        .default(current)
+        .items(&labels[..])
+        .interact()
+        .unwrap();
+
+    config.color_theme = themes[selection].0;
+    config.color_theme.apply();
+    save_config(&config);
+
     println!("Press Enter to continue...");
     std::io::stdin().read_line(&mut String::new()).unwrap();
     show_main_menu();
 }
 
 fn configure_threads() {
+    println!("{}", "\nDEFAULT THREAD COUNT".bright_magenta().bold());
+
+    let mut config = Config::load();
     println!(
         "{}",
-        "\nThread configuration coming soon!".bright_yellow()
+        format!("This machine has {} CPUs", num_cpus::get()).bright_cyan()
     );
+
+    let threads: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Default number of threads (or press Enter for auto)")
+        .with_initial_text(config.threads.map(|t| t.to_string()).unwrap_or_default())
+        .allow_empty(true)
+        .validate_with(|input: &String| -> Result<(), &str> {
+            match input.trim() {
+                "" => Ok(()),
+                value => match value.parse::<usize>() {
+                    Ok(n) if n > 0 => Ok(()),
+                    _ => Err("Enter a positive number"),
+                },
+            }
+        })
+        .interact_text()
+        .unwrap();
+
+    config.threads = threads.trim().parse().ok();
+    save_config(&config);
+
     println!("Press Enter to continue...");
     std::io::stdin().read_line(&mut String::new()).unwrap();
     show_main_menu();
@@ -274,13 +391,23 @@ fn configure_excludes() {
 
     // Get all common directories with descriptions
     let all_directories = get_all_common_directories();
+    let mut config = Config::load();
     
     println!("{}", "\nDirectories to EXCLUDE from searches:".bright_yellow());
     println!("{}", "   (All selected by default - DESELECT ones you want to search in)".bright_cyan());
     println!("{}", "   (Use SPACE to select/deselect, ENTER to confirm)".bright_cyan());
 
-    // All directories are selected by default
-    let defaults: Vec<bool> = vec![true; all_directories.len()];
+    // Start from the saved list, or everything selected if nothing is saved yet
+    let defaults: Vec<bool> = all_directories
+        .iter()
+        .map(|dir| {
+            let dir_name = dir.split(" - ").next().unwrap();
+            config
+                .excludes
+                .as_ref()
+                .is_none_or(|saved| saved.iter().any(|e| e == dir_name))
+        })
+        .collect();
 
     let selected = MultiSelect::with_theme(&ColorfulTheme::default())
         .with_prompt("Choose directories to exclude (deselect to search in them)")
@@ -289,17 +416,25 @@ fn configure_excludes() {
         .interact()
         .unwrap();
 
-    // Store the current exclude list for this session
-    let mut current_excludes = Vec::new();
+    // Keep any custom entries that are not in the common list
+    let common_names: Vec<&str> = all_directories
+        .iter()
+        .map(|dir| dir.split(" - ").next().unwrap())
+        .collect();
+    let mut current_excludes: Vec<String> = config
+        .excludes
+        .take()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|e| !common_names.contains(&e.as_str()))
+        .collect();
     for &index in &selected {
-        let dir_name = all_directories[index].split(" - ").next().unwrap();
-        current_excludes.push(dir_name.to_string());
-    }
-    
-    unsafe {
-        CURRENT_EXCLUDES = Some(current_excludes.clone());
+        current_excludes.push(common_names[index].to_string());
     }
 
+    config.excludes = Some(current_excludes);
+    save_config(&config);
+
     if selected.len() == all_directories.len() {
         println!("{}", "\nAll directories will be excluded (default behavior)".bright_red());
     } else if selected.is_empty() {
@@ -318,7 +453,6 @@ fn configure_excludes() {
                 println!("   • {}", dir_name.bright_green());
             }
         }
-        println!("{}", "\nThis configuration is active for current session only!".bright_cyan());
     }
     
     println!("\nPress Enter to continue...");
@@ -429,20 +563,34 @@ fn get_default_exclude_directories() -> Vec<String> {
 fn configure_progress() {
     println!("{}", "\nPROGRESS DISPLAY OPTIONS".bright_blue().bold());
 
-    let options = vec![
+    let options = [
         "Minimal - Just progress bar",
         "Standard - Progress + file count",
         "Detailed - Progress + current file names",
         "Verbose - All details + performance metrics",
     ];
 
+    let levels = [
+        ProgressLevel::Minimal,
+        ProgressLevel::Standard,
+        ProgressLevel::Detailed,
+        ProgressLevel::Verbose,
+    ];
+    let mut config = Config::load();
+    let current = levels
+        .iter()
+        .position(|level| *level == config.progress)
+        .unwrap_or(1);
+
     let selection = Select::with_theme(&ColorfulTheme::default())
         .with_prompt("Choose progress display level")
-        .default(1)
+        .default(current)
         .items(&options[..])
         .interact()
         .unwrap();
 
+    config.progress = levels[selection];
+    save_config(&config);
     println!(
         "{}",
         format!("Progress display set to: {}", options[selection]).bright_green()
@@ -452,6 +600,62 @@ fn configure_progress() {
     show_main_menu();
 }
 
+fn configure_default_flags() {
+    println!("{}", "\nDEFAULT SEARCH FLAGS".bright_magenta().bold());
+
+    let mut config = Config::load();
+    let flags = [
+        "Case insensitive search",
+        "Use regex patterns",
+        "Include hidden files",
+        "Ignore .gitignore/.ignore/.ffignore rules",
+        "Follow symbolic links",
+    ];
+    let defaults = [
+        config.defaults.ignore_case,
+        config.defaults.regex,
+        config.defaults.hidden,
+        config.defaults.no_ignore,
+        config.defaults.follow,
+    ];
+
+    let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Flags to turn on by default (SPACE to toggle, ENTER to confirm)")
+        .items(&flags[..])
+        .defaults(&defaults[..])
+        .interact()
+        .unwrap();
+
+    let extensions: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Default file extensions for grep (or press Enter for all)")
+        .with_initial_text(config.defaults.extensions.clone().unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()
+        .unwrap();
+
+    config.defaults.ignore_case = selected.contains(&0);
+    config.defaults.regex = selected.contains(&1);
+    config.defaults.hidden = selected.contains(&2);
+    config.defaults.no_ignore = selected.contains(&3);
+    config.defaults.follow = selected.contains(&4);
+    config.defaults.extensions = Some(extensions.trim().to_string()).filter(|e| !e.is_empty());
+    save_config(&config);
+
+    println!("Press Enter to continue...");
+    std::io::stdin().read_line(&mut String::new()).unwrap();
+    show_main_menu();
+}
+
+fn save_config(config: &Config) {
+    match config.save() {
+        Ok(path) => println!(
+            "{}",
+            format!("\nSettings saved to {}", path.display()).bright_green()
+        ),
+        Err(e) => println!("{}", format!("\nFailed to save settings: {}", e).bright_red()),
+    }
+}
+
 fn show_help() {
     println!("{}", "\nHELP & EXAMPLES".bright_blue().bold());
     println!("{}", "═".repeat(60).bright_blue());
@@ -460,27 +664,48 @@ fn show_help() {
     println!("  ffinder                    # Launch interactive mode");
     println!("  ff grep 'pattern'          # Direct grep search");
     println!("  ff find 'name'             # Direct file search");
+    println!("  ff tui ['query']           # Full-screen live search");
 
     println!("{}", "\nGREP EXAMPLES:".bright_yellow());
     println!("  ff grep 'main()' -d /projects");
     println!("  ff grep 'TODO|FIXME' -r -i");
-    println!("  ff grep 'error' -e 'log,txt' -t 8");
+    println!("  ff grep 'error' --ext 'log,txt' -t 8");
     println!("  ff grep 'function' -x 'node_modules,build'");
 
     println!("{}", "\nFILE SEARCH EXAMPLES:".bright_yellow());
-    println!("  ff find '*.rs' -r");
+    println!("  ff find '*.rs'");
+    println!("  ff find 'src/**/*.toml'");
+    println!("  ff find '^main\\.(rs|go)$' -r");
     println!("  ff find 'config' -i");
     println!("  ff find 'main.cpp' -d /projects");
+    println!("  ff find '*' --size +100M --newer 2d");
+    println!("  ff find 'migrations' --type d");
+
+    println!("{}", "\nLIVE SEARCH KEYS:".bright_yellow());
+    println!("  Tab                     Switch between grep and file names");
+    println!("  Ctrl-T / Ctrl-R         Toggle ignore case / regex");
+    println!("  Ctrl-E                  Edit the extension filter");
+    println!("  Enter / Esc             Pick the selected result / quit");
 
     println!("{}", "\nOPTIONS:".bright_yellow());
     println!("  -d, --dir <PATH>        Directory to search");
     println!("  -i, --ignore-case       Case insensitive");
     println!("  -r, --regex             Use regex patterns");
-    println!("  -e, --ext <EXTS>        File extensions");
+    println!("  -g, --glob              Glob file names (find; default for * ? [ {{)");
+    println!("  -F, --literal           Plain substring file names (find)");
+    println!("  --fuzzy                 Ranked fuzzy file names (find)");
+    println!("  --ext <EXTS>            File extensions");
     println!("  -t, --threads <NUM>     Thread count");
     println!("  -x, --exclude <DIRS>    Exclude directories");
     println!("  --progress              Show detailed progress");
 
+    println!("{}", "\nSETTINGS:".bright_yellow());
+    match Config::path() {
+        Some(path) => println!("  Saved in {}", path.display()),
+        None => println!("  No configuration directory available"),
+    }
+    println!("  Command-line flags override saved settings");
+
     println!("{}", "\nPERFORMANCE TIPS:".bright_yellow());
     println!("  • Use exclude directories for faster searches");
     println!("  • Specify file extensions to reduce scope");
@@ -492,12 +717,13 @@ fn show_help() {
     show_main_menu();
 }
 
+#[allow(clippy::too_many_arguments)]
 fn execute_grep_search(
     pattern: &str,
     directory: &str,
     case_insensitive: bool,
     use_regex: bool,
-    use_progress: bool,
+    progress: ProgressLevel,
     extensions: Option<&String>,
     thread_count: Option<&String>,
     exclude_common: bool,
@@ -506,81 +732,54 @@ fn execute_grep_search(
     println!("{}", "\nEXECUTING SEARCH...".bright_green().bold());
     println!("{}", "═".repeat(50).bright_blue());
 
-    let mut args = vec!["grep", pattern, "-d", directory];
-
-    if case_insensitive {
-        args.push("-i");
-    }
+    let config = Config::load();
+    let mut searcher = GrepSearcher::new(directory, case_insensitive, use_regex, extensions)
+        .with_progress(progress)
+        .with_walk_options(walk_options(&config));
 
-    if use_regex {
-        args.push("-r");
+    if let Some(threads) = config.threads {
+        searcher = searcher.with_threads(threads);
     }
-
-    if use_progress {
-        args.push("--progress");
-    }
-
-    if let Some(ext) = extensions {
-        if !ext.is_empty() {
-            args.extend_from_slice(&["-e", ext]);
-        }
-    }
-
     if let Some(threads) = thread_count {
-        if !threads.is_empty() {
-            args.extend_from_slice(&["-t", threads]);
+        match threads.trim().parse::<usize>() {
+            Ok(threads) => searcher = searcher.with_threads(threads),
+            Err(_) => println!(
+                "{}",
+                format!("Ignoring invalid thread count '{}'", threads).bright_yellow()
+            ),
         }
     }
 
     // Handle exclusions
-    let exclude_str;
-    if exclude_common || (additional_excludes.is_some() && !additional_excludes.unwrap().is_empty())
-    {
-        let mut exclude_list = Vec::new();
-        if exclude_common {
-            // Use session-specific exclude list if configured, otherwise use default
-            unsafe {
-                if let Some(ref current_excludes) = CURRENT_EXCLUDES {
-                    exclude_list.extend_from_slice(current_excludes);
-                } else {
-                    // Default exclude list if not configured
-                    exclude_list.extend(get_default_exclude_directories());
-                }
-            }
-        }
+    let mut exclude_list = Vec::new();
+    if exclude_common {
+        // Use the saved exclude list if configured, otherwise use default
+        exclude_list = config
+            .excludes
+            .unwrap_or_else(get_default_exclude_directories);
+    }
 
-        if let Some(additional) = additional_excludes {
-            if !additional.is_empty() {
-                for dir in additional.split(',') {
-                    exclude_list.push(dir.trim().to_string());
-                }
-            }
+    if let Some(additional) = additional_excludes {
+        for dir in additional.split(',') {
+            exclude_list.push(dir.trim().to_string());
         }
-
-        exclude_str = exclude_list.join(",");
-        args.push("-x");
-        args.push(&exclude_str);
     }
 
-    let result = Command::new("file_finder")
-        .args(&args)
-        .status();
+    if !exclude_list.is_empty() {
+        searcher = searcher.with_excludes(Some(&exclude_list.join(",")));
+    }
 
-    match result {
-        Ok(status) => {
-            if status.success() {
-                println!("{}", "\nSearch completed successfully!".bright_green());
-            } else {
-                println!("{}", "\nSearch failed!".bright_red());
+    let renderer = GrepRenderer;
+    renderer.print_header(&searcher, &[pattern.to_string()]);
+    match searcher.search(pattern) {
+        Ok(report) => {
+            renderer.print_report(&report);
+            print_errors(&report.errors, ErrorDisplay::default());
+            if progress == ProgressLevel::Verbose {
+                renderer.print_metrics(&report);
             }
         }
-        Err(e) => {
-            println!("{}", format!("\nFailed to execute: {}", e).bright_red());
-            println!(
-                "{}",
-                "Make sure FFinder is properly installed".bright_yellow()
-            );
-        }
+        Err(e) => println!("{}", format!("\nSearch failed: {}", e).bright_red()),
     }
 
     println!("\nPress Enter to return to main menu...");
@@ -588,45 +787,43 @@ fn execute_grep_search(
     show_main_menu();
 }
 
-fn execute_file_search(filename: &str, directory: &str, case_insensitive: bool, use_regex: bool) {
+fn execute_file_search(
+    filename: &str,
+    directory: &str,
+    case_insensitive: bool,
+    use_regex: bool,
+    entry_type: EntryType,
+) {
     println!("{}", "\nEXECUTING FILE SEARCH...".bright_cyan().bold());
     println!("{}", "═".repeat(50).bright_blue());
 
-    let mut args = vec!["find", filename, "-d", directory];
-
-    if case_insensitive {
-        args.push("-i");
-    }
-
-    if use_regex {
-        args.push("-r");
-    }
-
-    let result = Command::new("file_finder")
-        .args(&args)
-        .status();
-
-    match result {
-        Ok(status) => {
-            if status.success() {
-                println!(
-                    "{}",
-                    "\nFile search completed successfully!".bright_green()
-                );
-            } else {
-                println!("{}", "\nFile search failed!".bright_red());
-            }
-        }
-        Err(e) => {
-            println!("{}", format!("\nFailed to execute: {}", e).bright_red());
-            println!(
-                "{}",
-                "Make sure FFinder is properly installed".bright_yellow()
-            );
+    let filters = FileFilters {
+        entry_type,
+        ..FileFilters::default()
+    };
+    let searcher = FileSearcher::new(directory, case_insensitive, use_regex)
+        .with_filters(filters)
+        .with_walk_options(walk_options(&Config::load()));
+    let renderer = FileRenderer;
+    renderer.print_header(&searcher, filename);
+    match searcher.search(filename) {
+        Ok(report) => {
+            renderer.print_report(&report);
+            print_errors(&report.errors, ErrorDisplay::default());
         }
+        Err(e) => println!("{}", format!("\nFile search failed: {}", e).bright_red()),
     }
 
     println!("\nPress Enter to return to main menu...");
     std::io::stdin().read_line(&mut String::new()).unwrap();
     show_main_menu();
-}
\ No newline at end of file
+}
+
+fn walk_options(config: &Config) -> WalkOptions {
+    WalkOptions {
+        respect_ignore: !config.defaults.no_ignore,
+        hidden: config.defaults.hidden,
+        follow_links: config.defaults.follow,
+        ..WalkOptions::default()
+    }
+}
@@ -1,17 +1,129 @@
-use colored::*;
-use indicatif::{ProgressBar, ProgressStyle};
-use regex::Regex;
+use globset::{GlobBuilder, GlobMatcher};
+use regex::{Regex, RegexBuilder};
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
-use walkdir::WalkDir;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::utils::{format_file_size, format_modified_time, get_file_icon};
+use crate::archive::{self, ArchiveFormat};
+use crate::config::ProgressLevel;
+use crate::error::{PathError, SearchError};
+use crate::filter::{EntryType, FileFilters};
+use crate::fuzzy::{self, FuzzyMatch};
+use crate::utils::new_spinner;
+use crate::walk::{
+    build_walker, dangling_link, directory_usage, is_dangling, symlink_loop, walk_error,
+    SymlinkLoop, WalkOptions,
+};
 
 pub struct FileSearcher {
-    directory: PathBuf,
-    case_insensitive: bool,
-    use_regex: bool,
+    pub(crate) directory: PathBuf,
+    pub(crate) case_insensitive: bool,
+    pub(crate) syntax: NameSyntax,
+    pub(crate) walk: WalkOptions,
+    pub(crate) progress: ProgressLevel,
+    pub(crate) filters: FileFilters,
+    pub(crate) search_archives: bool,
+    // Set from another thread to stop the walk early
+    pub(crate) cancel: Option<Arc<AtomicBool>>,
+}
+
+// How `find` reads its pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameSyntax {
+    // Glob if the pattern contains glob metacharacters, literal otherwise
+    Auto,
+    // Substring of the file name
+    Literal,
+    // Glob against the file name or the path relative to the root
+    Glob,
+    // Regex against the file name
+    Regex,
+    // Ranked subsequence match against the path relative to the root
+    Fuzzy,
+}
+
+impl NameSyntax {
+    // The syntax `Auto` settles on for this pattern
+    pub fn resolve(self, pattern: &str) -> NameSyntax {
+        match self {
+            NameSyntax::Auto if pattern.contains(['*', '?', '[', '{']) => NameSyntax::Glob,
+            NameSyntax::Auto => NameSyntax::Literal,
+            syntax => syntax,
+        }
+    }
+}
+
+// A walked entry, or a dangling link the walk could only report as an error
+struct Entry {
+    path: PathBuf,
+    depth: usize,
+    // With --follow, the type of what a link points to
+    kind: EntryType,
+    is_link: bool,
+}
+
+enum NameMatcher {
+    Literal(String),
+    Glob(GlobMatcher),
+    Regex(Regex),
+    Fuzzy { query: String, case_sensitive: bool },
+}
+
+#[derive(Debug, Clone)]
+pub struct FileMatch {
+    pub path: PathBuf,
+    // File, Directory or Symlink
+    pub kind: EntryType,
+    // For directories, the total size of the files inside
+    pub size: Option<u64>,
+    pub modified: Option<SystemTime>,
+    // For directories, how many entries they contain at any depth
+    pub entries: Option<u64>,
+    // For symlinks, including ones followed with --follow, the resolved
+    // target (or the raw one if it doesn't resolve)
+    pub link_target: Option<PathBuf>,
+    // Score and matched characters (byte offsets into `path`) in fuzzy mode
+    pub fuzzy: Option<FuzzyMatch>,
+}
+
+impl FileMatch {
+    fn describe(path: &Path, kind: EntryType, is_link: bool, fuzzy: Option<FuzzyMatch>) -> Self {
+        let metadata = fs::metadata(path).ok();
+        let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+        // Directories are sized after the walk, all in one pass
+        let size = match kind {
+            EntryType::Directory => None,
+            _ => metadata.map(|m| m.len()),
+        };
+        let link_target = is_link
+            .then(|| fs::canonicalize(path).or_else(|_| fs::read_link(path)).ok())
+            .flatten();
+
+        FileMatch {
+            path: path.to_path_buf(),
+            kind,
+            size,
+            modified,
+            entries: None,
+            link_target,
+            fuzzy,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FileReport {
+    pub pattern: String,
+    pub files: Vec<FileMatch>,
+    pub entries_scanned: usize,
+    // Links that were not followed because they lead back to an ancestor
+    pub symlink_loops: Vec<SymlinkLoop>,
+    // Directories that couldn't be read, so whatever is inside went unseen
+    pub errors: Vec<PathError>,
+    pub elapsed: Duration,
 }
 
 impl FileSearcher {
@@ -19,168 +131,578 @@ impl FileSearcher {
         Self {
             directory: PathBuf::from(directory),
             case_insensitive,
-            use_regex,
+            syntax: if use_regex {
+                NameSyntax::Regex
+            } else {
+                NameSyntax::Auto
+            },
+            walk: WalkOptions::default(),
+            progress: ProgressLevel::default(),
+            filters: FileFilters::default(),
+            search_archives: false,
+            cancel: None,
         }
     }
 
-    pub fn search(&self, pattern: &str) {
-        println!(
-            "{} Searching for: {}",
-            "🔍".bright_yellow(),
-            pattern.bright_white().bold()
-        );
-        println!(
-            "{} Directory: {}",
-            "📁".bright_blue(),
-            self.directory.display().to_string().bright_cyan()
-        );
-        println!(
-            "{} Options: {} | {}",
-            "⚙️".bright_magenta(),
-            if self.case_insensitive {
-                "Case Insensitive".green()
-            } else {
-                "Case Sensitive".red()
-            },
-            if self.use_regex {
-                "Regex".green()
-            } else {
-                "Literal".yellow()
-            }
-        );
-        println!("{}", "─".repeat(80).bright_black());
+    pub fn with_syntax(mut self, syntax: NameSyntax) -> Self {
+        self.syntax = syntax;
+        self
+    }
 
-        // Create progress bar
-        let pb = ProgressBar::new_spinner();
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} {msg}")
-                .unwrap()
-                .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ "),
-        );
-        pb.set_message("Scanning directories...");
+    pub fn with_walk_options(mut self, walk: WalkOptions) -> Self {
+        self.walk = walk;
+        self
+    }
+
+    pub fn with_progress(mut self, progress: ProgressLevel) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn with_filters(mut self, filters: FileFilters) -> Self {
+        self.filters = filters;
+        self
+    }
 
-        let mut matches = Vec::new();
+    pub fn with_search_archives(mut self, search_archives: bool) -> Self {
+        self.search_archives = search_archives;
+        self
+    }
+
+    // Once `cancel` is set, `search` stops walking and reports what it has
+    pub fn with_cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn search(&self, pattern: &str) -> Result<FileReport, SearchError> {
+        let pb = new_spinner(self.progress, "Scanning directories...");
+
+        let start = Instant::now();
+        let mut files = Vec::new();
         let mut total_files = 0;
 
-        // Prepare search pattern
-        let search_pattern = if self.use_regex {
-            match Regex::new(pattern) {
-                Ok(regex) => Some(regex),
-                Err(e) => {
-                    println!("{} Invalid regex pattern: {}", "❌".red(), e);
-                    return;
-                }
-            }
-        } else {
-            None
-        };
+        let matcher = self.matcher(pattern)?;
 
-        // Walk through directory
-        for entry in WalkDir::new(&self.directory).into_iter().filter_map(|e| e.ok()) {
+        // Walk through directory, pruning below --max-depth
+        let walk_options = WalkOptions {
+            max_depth: self.filters.max_depth.or(self.walk.max_depth),
+            ..self.walk
+        };
+        let walk = build_walker(&self.directory, walk_options, None);
+        let mut symlink_loops = Vec::new();
+        let mut errors = Vec::new();
+        // Indexes into `files` of directories still to be sized and filtered
+        let mut directories = Vec::new();
+        for result in walk {
+            if self.cancelled() {
+                break;
+            }
+            let entry = match result {
+                Ok(entry) => Entry {
+                    path: entry.path().to_path_buf(),
+                    depth: entry.depth(),
+                    // Sockets, FIFOs and devices are never listed
+                    kind: match entry.file_type().and_then(EntryType::of) {
+                        Some(kind) => kind,
+                        None => continue,
+                    },
+                    is_link: entry.path_is_symlink(),
+                },
+                Err(err) => {
+                    if let Some(found) = symlink_loop(&err) {
+                        symlink_loops.push(found);
+                        continue;
+                    }
+                    // Dangling links are still worth listing when following
+                    let Some(path) = dangling_link(&err) else {
+                        errors.push(walk_error(&err, &self.directory));
+                        continue;
+                    };
+                    let depth = path
+                        .strip_prefix(&self.directory)
+                        .map_or(1, |p| p.components().count());
+                    Entry {
+                        path,
+                        depth,
+                        kind: EntryType::Symlink,
+                        is_link: true,
+                    }
+                }
+            };
             total_files += 1;
-            if total_files % 100 == 0 {
+            if total_files % 100 == 0 && self.progress != ProgressLevel::Minimal {
                 pb.set_message(format!("Scanned {} files...", total_files));
             }
 
-            if entry.file_type().is_file() {
-                let filename = entry.file_name().to_string_lossy();
-                let is_match = if let Some(ref regex) = search_pattern {
-                    regex.is_match(&filename)
-                } else if self.case_insensitive {
-                    filename.to_lowercase().contains(&pattern.to_lowercase())
-                } else {
-                    filename.contains(pattern)
-                };
-
-                if is_match {
-                    matches.push(entry.path().to_path_buf());
+            // A followed link counts as its target's type, and as a symlink
+            let wanted_type = if self.filters.broken_symlinks {
+                entry.is_link && is_dangling(&entry.path)
+            } else {
+                match self.filters.entry_type {
+                    EntryType::Any => true,
+                    EntryType::Symlink => entry.is_link,
+                    wanted => wanted == entry.kind,
+                }
+            };
+            let deep_enough = entry.depth >= self.filters.min_depth.unwrap_or(1);
+
+            if wanted_type && deep_enough {
+                if let Some(fuzzy) = self.match_name(&matcher, &entry.path) {
+                    let file = FileMatch::describe(&entry.path, entry.kind, entry.is_link, fuzzy);
+                    if file.kind == EntryType::Directory {
+                        directories.push(files.len());
+                        files.push(file);
+                    } else if self.filters.matches(file.size, file.modified) {
+                        files.push(file);
+                    }
+                }
+            }
+
+            // Archives are listed like directories of their members
+            let format = ArchiveFormat::of(&entry.path).filter(|_| self.search_archives);
+            if let (Some(format), EntryType::File) = (format, entry.kind) {
+                match self.search_archive(&entry, format, &matcher, &mut files) {
+                    Ok(scanned) => total_files += scanned,
+                    Err(e) => errors.push(PathError {
+                        path: entry.path.clone(),
+                        message: e.to_string(),
+                    }),
                 }
             }
         }
 
         pb.finish_and_clear();
 
-        // Display results
-        self.display_results(&matches, pattern);
+        let paths: Vec<PathBuf> = directories.iter().map(|&i| files[i].path.clone()).collect();
+        let usage = directory_usage(&paths);
+        for &i in &directories {
+            let (size, entries) = usage.get(&files[i].path).copied().unwrap_or_default();
+            files[i].size = Some(size);
+            files[i].entries = Some(entries);
+        }
+        // Only directories from the walk have `entries`; the rest are filtered
+        files.retain(|file| {
+            file.entries.is_none() || self.filters.matches(file.size, file.modified)
+        });
+
+        // Best fuzzy matches first, shorter paths breaking ties
+        if matches!(matcher, NameMatcher::Fuzzy { .. }) {
+            files.sort_by_cached_key(|file| {
+                let score = file.fuzzy.as_ref().map_or(0, |m| m.score);
+                (
+                    std::cmp::Reverse(score),
+                    file.path.as_os_str().len(),
+                    file.path.clone(),
+                )
+            });
+        }
+
+        Ok(FileReport {
+            pattern: pattern.to_string(),
+            files,
+            entries_scanned: total_files,
+            symlink_loops,
+            errors,
+            elapsed: start.elapsed(),
+        })
     }
 
-    fn display_results(&self, matches: &[PathBuf], pattern: &str) {
-        if matches.is_empty() {
-            println!(
-                "{} No files found matching: {}",
-                "😔".bright_red(),
-                pattern.bright_white().bold()
-            );
-            return;
+    fn cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    // Match the members of an archive as if it were a directory, returning
+    // how many there were
+    fn search_archive(
+        &self,
+        entry: &Entry,
+        format: ArchiveFormat,
+        matcher: &NameMatcher,
+        files: &mut Vec<FileMatch>,
+    ) -> io::Result<usize> {
+        let members = archive::members(&entry.path, format)?;
+        let scanned = members.len();
+        let base = entry.path.components().count() - entry.depth;
+        let max_depth = self.filters.max_depth.or(self.walk.max_depth);
+
+        for member in members {
+            let depth = member.path.components().count() - base;
+            let wanted_type = match self.filters.entry_type {
+                _ if self.filters.broken_symlinks => false,
+                EntryType::Any => true,
+                EntryType::Directory => member.is_dir,
+                EntryType::File => !member.is_dir,
+                EntryType::Symlink => false,
+            };
+            if !wanted_type
+                || depth < self.filters.min_depth.unwrap_or(1)
+                || max_depth.is_some_and(|max| depth > max)
+            {
+                continue;
+            }
+            let Some(fuzzy) = self.match_name(matcher, &member.path) else {
+                continue;
+            };
+            let size = (!member.is_dir).then_some(member.size);
+            if self.filters.matches(size, member.modified) {
+                files.push(FileMatch {
+                    path: member.path,
+                    kind: if member.is_dir {
+                        EntryType::Directory
+                    } else {
+                        EntryType::File
+                    },
+                    size,
+                    modified: member.modified,
+                    entries: None,
+                    link_target: None,
+                    fuzzy,
+                });
+            }
         }
+        Ok(scanned)
+    }
 
-        println!(
-            "{} Found {} match{}:",
-            "🎉".bright_green(),
-            matches.len().to_string().bright_yellow().bold(),
-            if matches.len() == 1 { "" } else { "es" }
-        );
-        println!("{}", "═".repeat(80).bright_blue());
+    // None if the name doesn't match, otherwise the fuzzy score if any
+    fn match_name(&self, matcher: &NameMatcher, path: &Path) -> Option<Option<FuzzyMatch>> {
+        let filename = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy();
+        let relative = path.strip_prefix(&self.directory).unwrap_or(path);
+        let is_match = match matcher {
+            NameMatcher::Literal(literal) if self.case_insensitive => {
+                filename.to_lowercase().contains(literal)
+            }
+            NameMatcher::Literal(literal) => filename.contains(literal.as_str()),
+            NameMatcher::Glob(glob) => {
+                glob.is_match(Path::new(filename.as_ref())) || glob.is_match(relative)
+            }
+            NameMatcher::Regex(regex) => regex.is_match(&filename),
+            NameMatcher::Fuzzy {
+                query,
+                case_sensitive,
+            } => {
+                // Score the relative path, but report positions in the full one
+                let full = path.to_string_lossy();
+                let relative = relative.to_string_lossy();
+                let offset = full.len().saturating_sub(relative.len());
+                let fuzzy = fuzzy::score(query, &relative, *case_sensitive).map(|mut m| {
+                    m.positions.iter_mut().for_each(|p| *p += offset);
+                    m
+                });
+                return fuzzy.map(Some);
+            }
+        };
+        is_match.then_some(None)
+    }
 
-        for (index, path) in matches.iter().enumerate() {
-            self.display_file_info(path, index + 1);
-            if index < matches.len() - 1 {
-                println!("{}", "─".repeat(80).bright_black());
+    fn matcher(&self, pattern: &str) -> Result<NameMatcher, SearchError> {
+        Ok(match self.syntax.resolve(pattern) {
+            NameSyntax::Glob => {
+                // `*` stays within one path component, `**` crosses them
+                let glob = GlobBuilder::new(pattern)
+                    .case_insensitive(self.case_insensitive)
+                    .literal_separator(true)
+                    .build()
+                    .map_err(SearchError::InvalidGlob)?;
+                NameMatcher::Glob(glob.compile_matcher())
             }
+            NameSyntax::Regex => NameMatcher::Regex(
+                RegexBuilder::new(pattern)
+                    .case_insensitive(self.case_insensitive)
+                    .build()?,
+            ),
+            // Smart case: an uppercase letter in the query makes it case sensitive
+            NameSyntax::Fuzzy => NameMatcher::Fuzzy {
+                query: pattern.to_string(),
+                case_sensitive: !self.case_insensitive && pattern.chars().any(char::is_uppercase),
+            },
+            _ if self.case_insensitive => NameMatcher::Literal(pattern.to_lowercase()),
+            _ => NameMatcher::Literal(pattern.to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::SizeFilter;
+
+    fn find(root: &Path, pattern: &str, syntax: NameSyntax) -> Vec<String> {
+        let searcher = FileSearcher::new(root.to_str().unwrap(), false, false).with_syntax(syntax);
+        let mut names: Vec<String> = searcher
+            .search(pattern)
+            .unwrap()
+            .files
+            .iter()
+            .map(|f| {
+                let relative = f.path.strip_prefix(root).unwrap();
+                relative.to_string_lossy().replace('\\', "/")
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_glob_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src").join("bin")).unwrap();
+        for file in [
+            "main.rs",
+            "src/lib.rs",
+            "src/bin/cli.rs",
+            "Cargo.toml",
+            "a*b.txt",
+        ] {
+            fs::write(root.join(file), "").unwrap();
         }
 
-        println!("{}", "═".repeat(80).bright_blue());
-        println!(
-            "{} Search completed. Found {} file{}.",
-            "✅".bright_green(),
-            matches.len().to_string().bright_yellow().bold(),
-            if matches.len() == 1 { "" } else { "s" }
+        let auto = NameSyntax::Auto;
+        assert_eq!(
+            find(root, "*.rs", auto),
+            vec!["main.rs", "src/bin/cli.rs", "src/lib.rs"]
         );
+        assert_eq!(find(root, "src/*.rs", auto), vec!["src/lib.rs"]);
+        assert_eq!(
+            find(root, "src/**/*.rs", auto),
+            vec!["src/bin/cli.rs", "src/lib.rs"]
+        );
+        assert_eq!(
+            find(root, "{main,lib}.?s", auto),
+            vec!["main.rs", "src/lib.rs"]
+        );
+        assert_eq!(find(root, "[CM]*", auto), vec!["Cargo.toml"]);
+        assert_eq!(find(root, "a*b", NameSyntax::Literal), vec!["a*b.txt"]);
+        assert_eq!(find(root, "lib", auto), vec!["src/lib.rs"]);
+
+        // -i applies to regexes too
+        let searcher = FileSearcher::new(root.to_str().unwrap(), true, true);
+        let report = searcher.search(r"^cargo\.").unwrap();
+        assert_eq!(report.files.len(), 1);
     }
 
-    fn display_file_info(&self, path: &Path, index: usize) {
-        let metadata = match fs::metadata(path) {
-            Ok(meta) => meta,
-            Err(_) => {
-                println!(
-                    "{} {}. {} {}",
-                    "❌".red(),
-                    index.to_string().bright_white().bold(),
-                    get_file_icon(path),
-                    path.display().to_string().bright_red()
-                );
-                return;
-            }
+    #[test]
+    fn test_metadata_predicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("a").join("b")).unwrap();
+        fs::write(root.join("small.txt"), "x").unwrap();
+        fs::write(root.join("a").join("big.txt"), vec![b'x'; 4096]).unwrap();
+        fs::write(root.join("a").join("b").join("deep.txt"), "x").unwrap();
+
+        let search = |filters: FileFilters| {
+            let searcher =
+                FileSearcher::new(root.to_str().unwrap(), false, false).with_filters(filters);
+            let mut names: Vec<String> = searcher
+                .search("*")
+                .unwrap()
+                .files
+                .iter()
+                .map(|f| f.path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        };
+
+        let big = FileFilters {
+            sizes: vec![SizeFilter::AtLeast(1024)],
+            ..FileFilters::default()
         };
+        assert_eq!(search(big), vec!["big.txt"]);
 
-        let filename = path.file_name().unwrap().to_string_lossy();
-        let directory = path.parent().unwrap_or(Path::new("")).display();
-        let size = format_file_size(metadata.len());
-        let modified = format_modified_time(metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH));
-
-        println!(
-            "{} {}. {} {}",
-            "📄".bright_blue(),
-            index.to_string().bright_white().bold(),
-            get_file_icon(path),
-            filename.bright_white().bold()
+        let shallow = FileFilters {
+            max_depth: Some(2),
+            ..FileFilters::default()
+        };
+        assert_eq!(search(shallow), vec!["big.txt", "small.txt"]);
+
+        let nested_dirs = FileFilters {
+            entry_type: EntryType::Directory,
+            min_depth: Some(2),
+            ..FileFilters::default()
+        };
+        assert_eq!(search(nested_dirs), vec!["b"]);
+    }
+
+    #[test]
+    fn test_directory_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let migrations = root.join("db").join("migrations");
+        fs::create_dir_all(migrations.join("old")).unwrap();
+        fs::write(migrations.join("001.sql"), "create table t;").unwrap();
+        fs::write(migrations.join("old").join("000.sql"), "-- x").unwrap();
+
+        let directories = FileFilters {
+            entry_type: EntryType::Directory,
+            ..FileFilters::default()
+        };
+        let report = FileSearcher::new(root.to_str().unwrap(), false, false)
+            .with_filters(directories.clone())
+            .search("migrations")
+            .unwrap();
+        assert_eq!(report.files.len(), 1);
+        let found = &report.files[0];
+        assert_eq!(found.path, migrations);
+        assert_eq!(found.kind, EntryType::Directory);
+        assert_eq!(found.entries, Some(3));
+        assert_eq!(found.size, Some(19));
+
+        // Nested directories share one walk, and are size-filtered after it
+        let report = FileSearcher::new(root.to_str().unwrap(), false, false)
+            .with_filters(FileFilters {
+                sizes: vec![SizeFilter::parse("+10").unwrap()],
+                ..directories
+            })
+            .search("*")
+            .unwrap();
+        let usage: Vec<_> = report.files.iter().map(|f| (f.size, f.entries)).collect();
+        assert_eq!(usage, vec![(Some(19), Some(4)), (Some(19), Some(3))]);
+
+        let any = FileFilters {
+            entry_type: EntryType::Any,
+            ..FileFilters::default()
+        };
+        let report = FileSearcher::new(root.to_str().unwrap(), false, false)
+            .with_filters(any)
+            .search("*")
+            .unwrap();
+        assert_eq!(report.files.len(), 5);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let target = root.join("real");
+        fs::create_dir_all(target.join("nested")).unwrap();
+        fs::write(target.join("data.txt"), "x").unwrap();
+        symlink(&target, root.join("farm")).unwrap();
+        symlink("..", target.join("nested").join("up")).unwrap();
+        symlink("missing.txt", root.join("dangling")).unwrap();
+
+        let follow = WalkOptions {
+            follow_links: true,
+            ..WalkOptions::default()
+        };
+        let report = FileSearcher::new(root.to_str().unwrap(), false, false)
+            .with_walk_options(follow)
+            .search("data.txt")
+            .unwrap();
+        let mut found: Vec<_> = report.files.iter().map(|f| f.path.clone()).collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![root.join("farm").join("data.txt"), target.join("data.txt")]
         );
-        
-        println!(
-            "   {} {}",
-            "📍".bright_yellow(),
-            directory.to_string().bright_cyan()
+        assert_eq!(report.symlink_loops.len(), 2);
+        assert!(report.symlink_loops.iter().all(|l| l.link.ends_with("up")));
+
+        // Dangling links are listed whether or not links are followed
+        for walk in [WalkOptions::default(), follow] {
+            let broken = FileFilters {
+                broken_symlinks: true,
+                ..FileFilters::default()
+            };
+            let report = FileSearcher::new(root.to_str().unwrap(), false, false)
+                .with_walk_options(walk)
+                .with_filters(broken)
+                .search("*")
+                .unwrap();
+            assert_eq!(report.files.len(), 1);
+            assert_eq!(report.files[0].path, root.join("dangling"));
+            assert_eq!(
+                report.files[0].link_target,
+                Some(PathBuf::from("missing.txt"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_cancelled_search_stops() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "").unwrap();
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let searcher = FileSearcher::new(dir.path().to_str().unwrap(), false, false)
+            .with_cancel(cancel.clone());
+        assert_eq!(searcher.search("a").unwrap().files.len(), 1);
+
+        cancel.store(true, Ordering::Relaxed);
+        let report = searcher.search("a").unwrap();
+        assert!(report.files.is_empty());
+        assert_eq!(report.entries_scanned, 0);
+    }
+
+    #[test]
+    fn test_search_archives() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let mut zip = zip::ZipWriter::new(fs::File::create(root.join("bundle.zip")).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("src/", options).unwrap();
+        zip.start_file("src/main.rs", options).unwrap();
+        zip.write_all(b"fn main() {}\n").unwrap();
+        zip.start_file("README.md", options).unwrap();
+        zip.finish().unwrap();
+
+        let gz = flate2::write::GzEncoder::new(
+            fs::File::create(root.join("release.tar.gz")).unwrap(),
+            flate2::Compression::default(),
         );
-        
-        println!(
-            "   {} {}  {} {}  {} {}",
-            "📏".bright_magenta(),
-            size.bright_white(),
-            "🕒".bright_green(),
-            modified.bright_white(),
-            "🔗".bright_blue(),
-            path.display().to_string().dimmed()
+        let mut tar = tar::Builder::new(gz);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(3);
+        header.set_mode(0o644);
+        tar.append_data(&mut header, "bin/tool.rs", &b"x\n\n"[..])
+            .unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let search = |pattern: &str, filters: FileFilters| {
+            let report = FileSearcher::new(root.to_str().unwrap(), false, false)
+                .with_filters(filters)
+                .with_search_archives(true)
+                .search(pattern)
+                .unwrap();
+            let mut names: Vec<String> = report
+                .files
+                .iter()
+                .map(|f| {
+                    let relative = f.path.strip_prefix(root).unwrap();
+                    relative.to_string_lossy().replace('\\', "/")
+                })
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(
+            search("*.rs", FileFilters::default()),
+            vec!["bundle.zip!/src/main.rs", "release.tar.gz!/bin/tool.rs"]
+        );
+        let dirs = FileFilters {
+            entry_type: EntryType::Directory,
+            ..FileFilters::default()
+        };
+        assert_eq!(search("src", dirs), vec!["bundle.zip!/src"]);
+        let shallow = FileFilters {
+            max_depth: Some(2),
+            ..FileFilters::default()
+        };
+        assert_eq!(
+            search("*", shallow),
+            vec!["bundle.zip", "bundle.zip!/README.md", "release.tar.gz"]
         );
     }
 }
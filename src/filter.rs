@@ -0,0 +1,178 @@
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::fs::FileType;
+use std::time::{Duration, SystemTime};
+
+// Metadata predicates for `find`; every one that is set must hold
+#[derive(Debug, Clone, Default)]
+pub struct FileFilters {
+    // All must match, so `+1M` and `-10M` together give a range
+    pub sizes: Vec<SizeFilter>,
+    // Modified at or after this time
+    pub newer: Option<SystemTime>,
+    // Modified before this time
+    pub older: Option<SystemTime>,
+    pub entry_type: EntryType,
+    // Depth 1 is the entries directly inside the search directory
+    pub min_depth: Option<usize>,
+    pub max_depth: Option<usize>,
+    // Only symlinks whose target doesn't exist, whatever `entry_type` says
+    pub broken_symlinks: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeFilter {
+    AtLeast(u64),
+    AtMost(u64),
+    Exactly(u64),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EntryType {
+    #[default]
+    File,
+    Directory,
+    Symlink,
+    // Any of the above; only meaningful as a filter
+    Any,
+}
+
+impl FileFilters {
+    // Size and time checks; depth and type are handled during the walk
+    pub fn matches(&self, size: Option<u64>, modified: Option<SystemTime>) -> bool {
+        let size_ok = self
+            .sizes
+            .iter()
+            .all(|filter| size.is_some_and(|size| filter.matches(size)));
+        let newer_ok = self
+            .newer
+            .is_none_or(|bound| modified.is_some_and(|time| time >= bound));
+        let older_ok = self
+            .older
+            .is_none_or(|bound| modified.is_some_and(|time| time < bound));
+        size_ok && newer_ok && older_ok
+    }
+}
+
+impl SizeFilter {
+    // `+10M` is at least 10 MiB, `-1k` at most 1 KiB, `512` exactly 512 bytes.
+    // Units are powers of 1024, matching how sizes are displayed.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (make, rest): (fn(u64) -> SizeFilter, &str) = if let Some(rest) = text.strip_prefix('+')
+        {
+            (SizeFilter::AtLeast, rest)
+        } else if let Some(rest) = text.strip_prefix('-') {
+            (SizeFilter::AtMost, rest)
+        } else {
+            (SizeFilter::Exactly, text)
+        };
+
+        let split = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (number, unit) = rest.split_at(split);
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid size '{}', expected e.g. +10M or -1k", text))?;
+        let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "k" | "kb" | "kib" => 1 << 10,
+            "m" | "mb" | "mib" => 1 << 20,
+            "g" | "gb" | "gib" => 1 << 30,
+            "t" | "tb" | "tib" => 1 << 40,
+            _ => return Err(format!("unknown size unit '{}' in '{}'", unit, text)),
+        };
+        Ok(make((number * multiplier as f64) as u64))
+    }
+
+    pub fn matches(self, size: u64) -> bool {
+        match self {
+            SizeFilter::AtLeast(bound) => size >= bound,
+            SizeFilter::AtMost(bound) => size <= bound,
+            SizeFilter::Exactly(bound) => size == bound,
+        }
+    }
+}
+
+impl EntryType {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "f" | "file" => Ok(EntryType::File),
+            "d" | "dir" | "directory" => Ok(EntryType::Directory),
+            "l" | "symlink" => Ok(EntryType::Symlink),
+            "any" => Ok(EntryType::Any),
+            _ => Err(format!("unknown type '{}', expected f, d, l or any", text)),
+        }
+    }
+
+    // The kind of a walked entry, without following symlinks
+    pub fn of(file_type: FileType) -> Option<Self> {
+        if file_type.is_symlink() {
+            Some(EntryType::Symlink)
+        } else if file_type.is_dir() {
+            Some(EntryType::Directory)
+        } else if file_type.is_file() {
+            Some(EntryType::File)
+        } else {
+            None
+        }
+    }
+}
+
+// A point in time, either relative to now (`30min`, `2h`, `2d`, `3w`) or a
+// local date (`2025-01-01`, `2025-01-01 12:30:00`)
+pub fn parse_time(text: &str) -> Result<SystemTime, String> {
+    let text = text.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return local_time(date.and_hms_opt(0, 0, 0).unwrap(), text);
+    }
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S") {
+        return local_time(datetime, text);
+    }
+
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let invalid = || format!("invalid time '{}', expected e.g. 2d or 2025-01-01", text);
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "s" | "sec" => 1,
+        "m" | "min" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    SystemTime::now()
+        .checked_sub(Duration::from_secs(number * seconds))
+        .ok_or_else(invalid)
+}
+
+fn local_time(datetime: NaiveDateTime, text: &str) -> Result<SystemTime, String> {
+    Local
+        .from_local_datetime(&datetime)
+        .earliest()
+        .map(SystemTime::from)
+        .ok_or_else(|| format!("'{}' does not exist in the local time zone", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_predicates() {
+        assert_eq!(SizeFilter::parse("+10M"), Ok(SizeFilter::AtLeast(10 << 20)));
+        assert_eq!(SizeFilter::parse("-1k"), Ok(SizeFilter::AtMost(1024)));
+        assert_eq!(SizeFilter::parse("512"), Ok(SizeFilter::Exactly(512)));
+        assert_eq!(SizeFilter::parse("1.5KB"), Ok(SizeFilter::Exactly(1536)));
+        assert!(SizeFilter::parse("+10X").is_err());
+
+        let two_days = parse_time("2d").unwrap();
+        let elapsed = SystemTime::now().duration_since(two_days).unwrap();
+        assert!(elapsed.as_secs() >= 2 * 24 * 60 * 60);
+        assert!(parse_time("2025-01-01").unwrap() < two_days);
+        assert!(parse_time("yesterday").is_err());
+    }
+}
@@ -0,0 +1,164 @@
+// Fuzzy path scoring in the style of fzf and VS Code's quick-open: the query
+// characters must appear in order, and the best-scoring alignment wins.
+
+const SCORE_MATCH: i64 = 16;
+const GAP_START: i64 = -3;
+const GAP_EXTENSION: i64 = -1;
+// Start of the path or right after a path separator
+const BONUS_SEPARATOR: i64 = 10;
+// Right after `_`, `-`, `.` or a space
+const BONUS_DELIMITER: i64 = 8;
+// camelCase hump or the start of a number
+const BONUS_CAMEL: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 6;
+// Per character matched in the file name rather than the directory
+const BONUS_FILENAME: i64 = 8;
+const FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    // Byte offsets of the matched characters in the candidate
+    pub positions: Vec<usize>,
+}
+
+// Score `candidate` against `query`, or None if the query characters don't
+// all appear in order
+pub fn score(query: &str, candidate: &str, case_sensitive: bool) -> Option<FuzzyMatch> {
+    let fold = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_lowercase().next().unwrap_or(c)
+        }
+    };
+    let query: Vec<char> = query.chars().map(fold).collect();
+    let chars: Vec<(usize, char)> = candidate.char_indices().collect();
+    let folded: Vec<char> = chars.iter().map(|&(_, c)| fold(c)).collect();
+
+    // Cheap subsequence check before the full alignment
+    let mut remaining = query.iter().peekable();
+    for c in &folded {
+        remaining.next_if(|q| *q == c);
+    }
+    if remaining.peek().is_some() {
+        return None;
+    }
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+
+    let name_start = candidate.rfind(['/', '\\']).map_or(0, |i| i + 1);
+    let bonus: Vec<i64> = chars
+        .iter()
+        .enumerate()
+        .map(|(j, &(byte, c))| {
+            let prev = j.checked_sub(1).map(|p| chars[p].1);
+            let boundary = match prev {
+                None | Some('/' | '\\') => BONUS_SEPARATOR,
+                Some('_' | '-' | '.' | ' ') => BONUS_DELIMITER,
+                Some(p) if p.is_lowercase() && c.is_uppercase() => BONUS_CAMEL,
+                Some(p) if !p.is_ascii_digit() && c.is_ascii_digit() => BONUS_CAMEL,
+                _ => 0,
+            };
+            let filename = if byte >= name_start {
+                BONUS_FILENAME
+            } else {
+                0
+            };
+            boundary + filename
+        })
+        .collect();
+
+    // best[i][j]: best score with query[..=i] matched and query[i] at j,
+    // with from[i][j] the position of query[i - 1] on that alignment
+    let (m, n) = (query.len(), chars.len());
+    let mut best: Vec<Option<i64>> = vec![None; m * n];
+    let mut from = vec![0; m * n];
+    for i in 0..m {
+        // Best previous row entry at least two columns back, with the gap
+        // penalty already applied for the current column
+        let mut gap: Option<(i64, usize)> = None;
+        for j in i..n {
+            if i > 0 && j >= 2 {
+                gap = gap.map(|(score, k)| (score + GAP_EXTENSION, k));
+                if let Some(prev) = best[(i - 1) * n + j - 2] {
+                    if gap.is_none_or(|(score, _)| prev + GAP_START > score) {
+                        gap = Some((prev + GAP_START, j - 2));
+                    }
+                }
+            }
+            if folded[j] != query[i] {
+                continue;
+            }
+            if i == 0 {
+                best[j] = Some(SCORE_MATCH + bonus[j] * FIRST_CHAR_MULTIPLIER);
+                continue;
+            }
+
+            let consecutive = best[(i - 1) * n + j - 1].map(|s| (s + BONUS_CONSECUTIVE, j - 1));
+            let step = match (consecutive, gap) {
+                (Some(c), Some(g)) => Some(if c.0 >= g.0 { c } else { g }),
+                (c, g) => c.or(g),
+            };
+            if let Some((score, prev)) = step {
+                best[i * n + j] = Some(score + SCORE_MATCH + bonus[j]);
+                from[i * n + j] = prev;
+            }
+        }
+    }
+
+    let last = (m - 1) * n;
+    let (mut j, score) = (0..n)
+        .filter_map(|j| best[last + j].map(|score| (j, score)))
+        .max_by_key(|&(j, score)| (score, std::cmp::Reverse(j)))?;
+    let mut positions = vec![0; m];
+    for i in (0..m).rev() {
+        positions[i] = chars[j].0;
+        j = from[i * n + j];
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rank<'a>(query: &str, candidates: &[&'a str]) -> Vec<&'a str> {
+        let mut scored: Vec<(i64, &str)> = candidates
+            .iter()
+            .filter_map(|c| score(query, c, false).map(|m| (m.score, *c)))
+            .collect();
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, c)| c).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_ranking() {
+        let candidates = [
+            "src/ui/sidebar/recent_tools.rs",
+            "src/user_controller.rs",
+            "docs/unsorted/rectangle.md",
+            "src/main.rs",
+        ];
+        assert_eq!(rank("usrctl", &candidates)[0], "src/user_controller.rs");
+        assert!(score("usrctl", "src/main.rs", false).is_none());
+
+        // File name matches beat directory matches
+        assert_eq!(
+            rank("main", &["main/lib.rs", "src/main.rs"]),
+            vec!["src/main.rs", "main/lib.rs"]
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_positions() {
+        let m = score("uc", "src/user_controller.rs", false).unwrap();
+        assert_eq!(m.positions, vec![4, 9]);
+        assert!(score("UC", "src/user_controller.rs", true).is_none());
+    }
+}
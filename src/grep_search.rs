@@ -1,28 +1,339 @@
-use colored::*;
-use indicatif::{ProgressBar, ProgressStyle};
-use regex::Regex;
-use std::collections::HashSet;
+use crossbeam_channel::{Receiver, RecvTimeoutError, TryRecvError};
+use encoding_rs::Encoding;
+use memmap2::Mmap;
+use rayon::ThreadPool;
+use regex::bytes::Regex;
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, Read};
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::utils::{get_file_icon, is_binary_file, should_search_file};
+use crate::archive::{self, ArchiveFormat};
+use crate::config::ProgressLevel;
+use crate::decode::{self, has_utf16_bom};
+use crate::decompress::{self, Compression};
+use crate::error::{PathError, SearchError};
+use crate::extract::{Extractor, Segment};
+use crate::patterns::{Matcher, Query};
+use crate::utils::{
+    has_binary_magic, looks_binary, new_spinner, parse_list, read_sample, should_search_file,
+    ExcludeFilter, BINARY_SAMPLE_SIZE,
+};
+use crate::walk::{
+    build_walker, dangling_link, symlink_loop, walk_error, SymlinkLoop, WalkOptions,
+};
 
 pub struct GrepSearcher {
-    directory: PathBuf,
-    case_insensitive: bool,
-    use_regex: bool,
-    extensions: Option<HashSet<String>>,
+    pub(crate) directory: PathBuf,
+    pub(crate) case_insensitive: bool,
+    pub(crate) use_regex: bool,
+    pub(crate) extensions: Option<HashSet<String>>,
+    pub(crate) threads: usize,
+    pub(crate) excludes: Vec<String>,
+    pub(crate) progress: ProgressLevel,
+    pub(crate) before_context: usize,
+    pub(crate) after_context: usize,
+    pub(crate) walk: WalkOptions,
+    // Used for files without a byte order mark; None searches their raw bytes
+    pub(crate) encoding: Option<&'static Encoding>,
+    pub(crate) mmap: MmapChoice,
+    pub(crate) binary: BinaryMode,
+    pub(crate) search_zip: bool,
+    pub(crate) search_archives: bool,
+    pub(crate) extractors: Vec<Arc<dyn Extractor>>,
+    // Select the lines that don't match instead
+    pub(crate) invert: bool,
+    pub(crate) output: OutputMode,
+    // Only files with every `all_of` pattern and no `none_of` pattern
+    pub(crate) all_of: Vec<String>,
+    pub(crate) none_of: Vec<String>,
 }
 
-#[derive(Debug)]
+// Whether grep memory-maps a file or streams it through a buffered reader
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MmapChoice {
+    // Map files of at least `MMAP_THRESHOLD` bytes
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+// Below this, setting up a map costs more than streaming the file
+pub const MMAP_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+// What grep does with files that look binary
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BinaryMode {
+    // Leave them out
+    #[default]
+    Skip,
+    // Search the raw bytes and report where they match, not the lines
+    Search,
+    // Search them line by line like any text file
+    Text,
+}
+
+impl BinaryMode {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "skip" => Ok(BinaryMode::Skip),
+            "search" => Ok(BinaryMode::Search),
+            "text" => Ok(BinaryMode::Text),
+            _ => Err(format!(
+                "unknown binary mode '{}', expected skip, search or text",
+                text
+            )),
+        }
+    }
+}
+
+// What grep reports for each file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    // Every selected line, grouped by file
+    #[default]
+    Lines,
+    // How many lines each file has selected
+    Count,
+    // Files with a selected line; reading a file stops at the first
+    FilesWithMatches,
+    // Files without one; reading a file stops at the first
+    FilesWithoutMatch,
+}
+
+// Offsets kept per binary file; the count covers the rest
+const MAX_BINARY_OFFSETS: usize = 100;
+
+#[derive(Debug, Clone)]
 pub struct Match {
-    file_path: PathBuf,
-    line_number: usize,
-    line_content: String,
-    match_start: usize,
-    match_end: usize,
+    pub file_path: PathBuf,
+    pub line_number: usize,
+    // Byte offset of the start of the line within the file
+    pub byte_offset: u64,
+    // Invalid UTF-8 is shown as U+FFFD; the match range indexes this string
+    pub line_content: String,
+    pub match_start: usize,
+    pub match_end: usize,
+    // The line as searched, kept only when it isn't valid UTF-8
+    pub raw_line: Option<Vec<u8>>,
+    // The match range in the searched bytes, which only differs from
+    // `match_start..match_end` when `raw_line` is kept
+    pub raw_start: usize,
+    pub raw_end: usize,
+    // Where in a document the line was extracted from: `page 3`,
+    // `Sheet1!B4`, `slide 2`
+    pub location: Option<String>,
+    // Index into `GrepReport::patterns` of the pattern that matched
+    pub pattern: usize,
+}
+
+// A non-matching line printed around a match with -A/-B/-C
+#[derive(Debug, Clone)]
+pub struct ContextLine {
+    pub file_path: PathBuf,
+    pub line_number: usize,
+    pub byte_offset: u64,
+    pub line_content: String,
+    // The line as searched, kept only when it isn't valid UTF-8
+    pub raw_line: Option<Vec<u8>>,
+}
+
+// A binary file with matches, reported by byte offset with --binary=search
+#[derive(Debug, Clone)]
+pub struct BinaryMatch {
+    pub file_path: PathBuf,
+    // Where the first matches start, in file order
+    pub offsets: Vec<u64>,
+    pub count: usize,
+}
+
+// One file's total with -c, -l or -L
+#[derive(Debug, Clone)]
+pub struct FileCount {
+    pub file_path: PathBuf,
+    // Selected lines, or matches in a binary file. With -l this stops at 1,
+    // and with -L it is always 0.
+    pub count: usize,
+}
+
+#[derive(Debug)]
+pub(crate) enum LineEvent {
+    Match(Match),
+    Context(ContextLine),
+    Binary(BinaryMatch),
+    Count(FileCount),
+}
+
+impl Match {
+    // The bytes the match range `raw_start..raw_end` indexes
+    pub fn line_bytes(&self) -> &[u8] {
+        self.raw_line
+            .as_deref()
+            .unwrap_or(self.line_content.as_bytes())
+    }
+}
+
+impl ContextLine {
+    pub fn line_bytes(&self) -> &[u8] {
+        self.raw_line
+            .as_deref()
+            .unwrap_or(self.line_content.as_bytes())
+    }
+}
+
+impl LineEvent {
+    fn file_path(&self) -> &Path {
+        match self {
+            LineEvent::Match(m) => &m.file_path,
+            LineEvent::Context(line) => &line.file_path,
+            LineEvent::Binary(binary) => &binary.file_path,
+            LineEvent::Count(count) => &count.file_path,
+        }
+    }
+}
+
+// Counts the bytes read through it
+struct Counted<R> {
+    inner: R,
+    bytes: u64,
+}
+
+impl<R: Read> Read for Counted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes += n as u64;
+        Ok(n)
+    }
+}
+
+#[derive(Debug)]
+pub struct GrepReport {
+    // The patterns, or a summary of them if there are several
+    pub pattern: String,
+    // Every pattern whose matches are shown, in the order given
+    pub patterns: Vec<String>,
+    pub matches: Vec<Match>,
+    // Each context line appears once, even where context windows overlap
+    pub context: Vec<ContextLine>,
+    // Binary files that matched, in path order, with --binary=search
+    pub binary_matches: Vec<BinaryMatch>,
+    // With -c, -l and -L instead of `matches`, in path order
+    pub file_counts: Vec<FileCount>,
+    // Whether `matches` are the lines that didn't match, with -v
+    pub inverted: bool,
+    pub files_searched: usize,
+    pub bytes_searched: u64,
+    // Bytes searched in each file with results, in path order. An archive
+    // member counts its own bytes.
+    pub file_bytes: BTreeMap<PathBuf, u64>,
+    // Links that were not followed because they lead back to an ancestor
+    pub symlink_loops: Vec<SymlinkLoop>,
+    // Directories and files that couldn't be read, in path order
+    pub errors: Vec<PathError>,
+    pub elapsed: Duration,
+}
+
+#[derive(Default)]
+struct StreamState {
+    files_searched: AtomicUsize,
+    bytes_searched: AtomicU64,
+    file_bytes: Mutex<BTreeMap<PathBuf, u64>>,
+    current_file: Mutex<Option<PathBuf>>,
+    symlink_loops: Mutex<Vec<SymlinkLoop>>,
+    errors: Mutex<Vec<PathError>>,
+    cancelled: AtomicBool,
+}
+
+// Iterator returned by `GrepSearcher::matches`
+pub struct Matches {
+    receiver: Receiver<Result<LineEvent, SearchError>>,
+    state: Arc<StreamState>,
+}
+
+impl Matches {
+    pub fn files_searched(&self) -> usize {
+        self.state.files_searched.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_searched(&self) -> u64 {
+        self.state.bytes_searched.load(Ordering::Relaxed)
+    }
+
+    // Symlink loops the walk has run into so far
+    pub fn symlink_loops(&self) -> Vec<SymlinkLoop> {
+        self.state.symlink_loops.lock().unwrap().clone()
+    }
+
+    // Paths that couldn't be walked or read so far
+    pub fn errors(&self) -> Vec<PathError> {
+        self.state.errors.lock().unwrap().clone()
+    }
+
+    // Non-blocking `next`: `Pending` while the search is still running but
+    // nothing new has been found, `Ready(None)` once it has finished
+    pub fn poll_next(&mut self) -> Poll<Option<Result<Match, SearchError>>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Ok(LineEvent::Match(m))) => return Poll::Ready(Some(Ok(m))),
+                Ok(Ok(LineEvent::Context(_) | LineEvent::Binary(_) | LineEvent::Count(_))) => {
+                    continue
+                }
+                Ok(Err(e)) => return Poll::Ready(Some(Err(e))),
+                Err(TryRecvError::Empty) => return Poll::Pending,
+                Err(TryRecvError::Disconnected) => return Poll::Ready(None),
+            }
+        }
+    }
+
+    fn current_file(&self) -> Option<PathBuf> {
+        self.state.current_file.lock().unwrap().clone()
+    }
+}
+
+impl Iterator for Matches {
+    type Item = Result<Match, SearchError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.receiver.recv().ok()? {
+                Ok(LineEvent::Match(m)) => return Some(Ok(m)),
+                Ok(LineEvent::Context(_) | LineEvent::Binary(_) | LineEvent::Count(_)) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl Drop for Matches {
+    fn drop(&mut self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl GrepReport {
+    // Matches grouped by file, in path order
+    pub fn files(&self) -> BTreeMap<&Path, Vec<&Match>> {
+        let mut files: BTreeMap<&Path, Vec<&Match>> = BTreeMap::new();
+        for m in &self.matches {
+            files.entry(&m.file_path).or_default().push(m);
+        }
+        files
+    }
+
+    // Context lines grouped by file, in line order
+    pub fn context_by_file(&self) -> BTreeMap<&Path, Vec<&ContextLine>> {
+        let mut files: BTreeMap<&Path, Vec<&ContextLine>> = BTreeMap::new();
+        for line in &self.context {
+            files.entry(&line.file_path).or_default().push(line);
+        }
+        files
+    }
 }
 
 impl GrepSearcher {
@@ -44,221 +355,1313 @@ impl GrepSearcher {
             case_insensitive,
             use_regex,
             extensions,
+            threads: num_cpus::get(),
+            excludes: Vec::new(),
+            progress: ProgressLevel::default(),
+            before_context: 0,
+            after_context: 0,
+            walk: WalkOptions::default(),
+            encoding: None,
+            mmap: MmapChoice::default(),
+            binary: BinaryMode::default(),
+            search_zip: false,
+            search_archives: false,
+            extractors: Vec::new(),
+            invert: false,
+            output: OutputMode::default(),
+            all_of: Vec::new(),
+            none_of: Vec::new(),
         }
     }
 
-    pub fn search(&self, pattern: &str) {
-        println!(
-            "{} Searching for pattern: {}",
-            "🔎".bright_yellow(),
-            pattern.bright_white().bold()
-        );
-        println!(
-            "{} Directory: {}",
-            "📁".bright_blue(),
-            self.directory.display().to_string().bright_cyan()
-        );
-        
-        let ext_info = if let Some(ref exts) = self.extensions {
-            format!("Extensions: {}", exts.iter().cloned().collect::<Vec<_>>().join(", ")).bright_magenta()
-        } else {
-            "All files".bright_magenta()
-        };
-        
-        println!(
-            "{} Options: {} | {} | {}",
-            "⚙️".bright_magenta(),
-            if self.case_insensitive {
-                "Case Insensitive".green()
-            } else {
-                "Case Sensitive".red()
-            },
-            if self.use_regex {
-                "Regex".green()
-            } else {
-                "Literal".yellow()
-            },
-            ext_info
-        );
-        println!("{}", "─".repeat(80).bright_black());
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
 
-        // Create progress bar
-        let pb = ProgressBar::new_spinner();
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} {msg}")
-                .unwrap()
-                .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ "),
-        );
-        pb.set_message("Scanning files...");
+    pub fn with_excludes(mut self, excludes: Option<&String>) -> Self {
+        self.excludes = excludes.map(|list| parse_list(list)).unwrap_or_default();
+        self
+    }
+
+    pub fn with_progress(mut self, progress: ProgressLevel) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn with_context(mut self, before: usize, after: usize) -> Self {
+        self.before_context = before;
+        self.after_context = after;
+        self
+    }
+
+    pub fn with_walk_options(mut self, walk: WalkOptions) -> Self {
+        self.walk = walk;
+        self
+    }
+
+    pub fn with_encoding(mut self, encoding: Option<&'static Encoding>) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn with_mmap(mut self, mmap: MmapChoice) -> Self {
+        self.mmap = mmap;
+        self
+    }
+
+    pub fn with_binary(mut self, binary: BinaryMode) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    pub fn with_search_zip(mut self, search_zip: bool) -> Self {
+        self.search_zip = search_zip;
+        self
+    }
+
+    pub fn with_search_archives(mut self, search_archives: bool) -> Self {
+        self.search_archives = search_archives;
+        self
+    }
+
+    pub fn with_invert_match(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    pub fn with_output(mut self, output: OutputMode) -> Self {
+        self.output = output;
+        self
+    }
 
+    // Only search files that contain every one of these patterns. Without
+    // any other pattern, their matches are the ones shown.
+    pub fn with_all_of(mut self, patterns: Vec<String>) -> Self {
+        self.all_of = patterns;
+        self
+    }
+
+    // Only search files that contain none of these patterns
+    pub fn with_none_of(mut self, patterns: Vec<String>) -> Self {
+        self.none_of = patterns;
+        self
+    }
+
+    // Search files this extractor handles through the text it extracts.
+    // Extractors are tried in the order they were added.
+    pub fn with_extractor(mut self, extractor: impl Extractor + 'static) -> Self {
+        self.extractors.push(Arc::new(extractor));
+        self
+    }
+
+    pub fn search(&self, pattern: &str) -> Result<GrepReport, SearchError> {
+        self.search_any(&[pattern.to_string()])
+    }
+
+    // Search for lines matching any of several patterns. Each match says
+    // which pattern it was.
+    pub fn search_any(&self, patterns: &[String]) -> Result<GrepReport, SearchError> {
+        let pb = new_spinner(self.progress, "Scanning files...");
+
+        let start = Instant::now();
+        let stream = self.matches_any(patterns);
         let mut all_matches = Vec::new();
-        let mut files_searched = 0;
+        let mut context = Vec::new();
+        let mut binary_matches = Vec::new();
+        let mut file_counts = Vec::new();
+        loop {
+            match stream.receiver.recv_timeout(Duration::from_millis(100)) {
+                Ok(Ok(LineEvent::Match(m))) => all_matches.push(m),
+                Ok(Ok(LineEvent::Context(line))) => context.push(line),
+                Ok(Ok(LineEvent::Binary(binary))) => binary_matches.push(binary),
+                Ok(Ok(LineEvent::Count(count))) => file_counts.push(count),
+                Ok(Err(e)) => {
+                    pb.finish_and_clear();
+                    return Err(e);
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            match (self.progress, stream.current_file()) {
+                (ProgressLevel::Off | ProgressLevel::Minimal, _) => {}
+                (_, Some(path)) => pb.set_message(format!(
+                    "Searched {} files... {}",
+                    stream.files_searched(),
+                    path.display()
+                )),
+                (_, None) => pb.set_message(format!(
+                    "Searched {} files...",
+                    stream.files_searched()
+                )),
+            }
+        }
 
-        // Prepare search pattern
-        let search_regex = if self.use_regex {
-            match Regex::new(pattern) {
-                Ok(regex) => regex,
-                Err(e) => {
-                    println!("{} Invalid regex pattern: {}", "❌".red(), e);
-                    return;
+        // Workers finish in any order, keep the output stable
+        all_matches.sort_by(|a, b| {
+            (&a.file_path, a.line_number, a.match_start).cmp(&(
+                &b.file_path,
+                b.line_number,
+                b.match_start,
+            ))
+        });
+        context.sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        binary_matches.sort_by(|a: &BinaryMatch, b| a.file_path.cmp(&b.file_path));
+        file_counts.sort_by(|a: &FileCount, b| a.file_path.cmp(&b.file_path));
+        let mut errors = stream.errors();
+        errors.sort_by(|a, b| a.path.cmp(&b.path));
+        let file_bytes = std::mem::take(&mut *stream.state.file_bytes.lock().unwrap());
+
+        pb.finish_and_clear();
+
+        let patterns = self.shown(patterns).to_vec();
+        Ok(GrepReport {
+            pattern: describe(&patterns),
+            patterns,
+            matches: all_matches,
+            context,
+            binary_matches,
+            file_counts,
+            inverted: self.invert,
+            files_searched: stream.files_searched(),
+            bytes_searched: stream.bytes_searched(),
+            file_bytes,
+            symlink_loops: stream.symlink_loops(),
+            errors,
+            elapsed: start.elapsed(),
+        })
+    }
+
+    // Lazily stream matches as the workers find them. Files are searched in
+    // parallel, so matches from different files arrive in no particular order;
+    // matches within one file stay in line order. Dropping the iterator stops
+    // the walk.
+    pub fn matches(&self, pattern: &str) -> Matches {
+        self.matches_any(&[pattern.to_string()])
+    }
+
+    pub fn matches_any(&self, patterns: &[String]) -> Matches {
+        let (match_tx, match_rx) = crossbeam_channel::bounded(self.threads * 256);
+        let state = Arc::new(StreamState::default());
+        let stream = Matches {
+            receiver: match_rx,
+            state: Arc::clone(&state),
+        };
+
+        let (search, exclude_filter, pool) = match self.prepare(patterns) {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                let _ = match_tx.send(Err(e));
+                return stream;
+            }
+        };
+
+        // The walker feeds paths to the worker pool, workers stream matches back
+        let (path_tx, path_rx) = crossbeam_channel::bounded::<PathBuf>(self.threads * 64);
+
+        for _ in 0..self.threads {
+            let path_rx = path_rx.clone();
+            let match_tx = match_tx.clone();
+            let search = search.clone();
+            let state = Arc::clone(&state);
+            let show_progress = matches!(
+                self.progress,
+                ProgressLevel::Detailed | ProgressLevel::Verbose
+            );
+            pool.spawn(move || {
+                for path in path_rx {
+                    if state.cancelled.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    if show_progress {
+                        *state.current_file.lock().unwrap() = Some(path.clone());
+                    }
+                    // Matches before a read error are still reported
+                    let mut events = Vec::new();
+                    let mut members = Vec::new();
+                    let result = search.file(&path, &mut events, &mut members);
+                    let events = search.summarize(&path, events, matches!(result, Ok(Some(_))));
+                    // Skipped binary files don't count as searched
+                    if !matches!(result, Ok(None)) {
+                        state.files_searched.fetch_add(1, Ordering::Relaxed);
+                    }
+                    if let Ok(Some(len)) = result {
+                        state.bytes_searched.fetch_add(len, Ordering::Relaxed);
+                        members.push((path.clone(), len));
+                    }
+                    if !events.is_empty() {
+                        let shown: HashSet<&Path> =
+                            events.iter().map(LineEvent::file_path).collect();
+                        let mut file_bytes = state.file_bytes.lock().unwrap();
+                        for (file, bytes) in members {
+                            if shown.contains(file.as_path()) {
+                                file_bytes.insert(file, bytes);
+                            }
+                        }
+                    }
+                    for event in events {
+                        if match_tx.send(Ok(event)).is_err() {
+                            return;
+                        }
+                    }
+                    if let Err(e) = result {
+                        state.errors.lock().unwrap().push(PathError {
+                            path,
+                            message: e.to_string(),
+                        });
+                    }
+                }
+            });
+        }
+
+        let walk = build_walker(&self.directory, self.walk, Some(exclude_filter));
+        let extensions = self.extensions.clone();
+        let search_archives = self.search_archives;
+        let root = self.directory.clone();
+        thread::spawn(move || {
+            for result in walk {
+                if state.cancelled.load(Ordering::Relaxed) {
+                    break;
+                }
+                let entry = match result {
+                    Ok(entry) => entry,
+                    Err(err) => {
+                        if let Some(found) = symlink_loop(&err) {
+                            state.symlink_loops.lock().unwrap().push(found);
+                        } else if dangling_link(&err).is_none() {
+                            state.errors.lock().unwrap().push(walk_error(&err, &root));
+                        }
+                        continue;
+                    }
+                };
+                if entry.file_type().is_some_and(|t| t.is_file()) {
+                    let path = entry.path();
+
+                    // Archive members are filtered by extension as they're read
+                    let archive = search_archives && ArchiveFormat::of(path).is_some();
+                    if !archive && !should_search_file(path, &extensions) {
+                        continue;
+                    }
+
+                    if path_tx.send(path.to_path_buf()).is_err() {
+                        break;
+                    }
                 }
             }
+        });
+
+        stream
+    }
+
+    fn final_pattern(&self, pattern: &str) -> String {
+        let pattern = if self.use_regex {
+            pattern.to_string()
         } else {
-            let escaped_pattern = regex::escape(pattern);
-            let final_pattern = if self.case_insensitive {
-                format!("(?i){}", escaped_pattern)
-            } else {
-                escaped_pattern
+            regex::escape(pattern)
+        };
+        if self.case_insensitive {
+            format!("(?i){}", pattern)
+        } else {
+            pattern
+        }
+    }
+
+    // The pattern as grep matches it against each line
+    pub(crate) fn line_regex(&self, pattern: &str) -> Result<Regex, SearchError> {
+        Ok(Regex::new(&self.final_pattern(pattern))?)
+    }
+
+    // The `--all-of` patterns stand in when there are no others
+    pub(crate) fn shown<'a>(&'a self, patterns: &'a [String]) -> &'a [String] {
+        match patterns {
+            [] => &self.all_of,
+            _ => patterns,
+        }
+    }
+
+    // Plain strings can all go through one Aho-Corasick automaton, unless
+    // case folding needs more than ASCII
+    fn literal_set(&self, patterns: &[String]) -> bool {
+        let needs_unicode = self.case_insensitive && patterns.iter().any(|p| !p.is_ascii());
+        !self.use_regex && !needs_unicode
+    }
+
+    fn matcher(&self, patterns: &[String]) -> Result<Matcher, SearchError> {
+        // A single pattern stays a regex, which handles one literal as well
+        if patterns.is_empty() || (patterns.len() > 1 && self.literal_set(patterns)) {
+            return Matcher::literals(patterns, self.case_insensitive);
+        }
+        let sources: Vec<String> = patterns.iter().map(|p| self.final_pattern(p)).collect();
+        Matcher::regexes(&sources)
+    }
+
+    fn query(&self) -> Result<Option<Query>, SearchError> {
+        if self.all_of.is_empty() && self.none_of.is_empty() {
+            return Ok(None);
+        }
+        let query = if self.literal_set(&[&self.all_of[..], &self.none_of[..]].concat()) {
+            Query::literals(&self.all_of, &self.none_of, self.case_insensitive)?
+        } else {
+            let sources = |patterns: &[String]| -> Vec<String> {
+                patterns.iter().map(|p| self.final_pattern(p)).collect()
             };
-            match Regex::new(&final_pattern) {
-                Ok(regex) => regex,
-                Err(e) => {
-                    println!("{} Error creating search pattern: {}", "❌".red(), e);
-                    return;
-                }
-            }
+            Query::regexes(&sources(&self.all_of), &sources(&self.none_of))?
         };
+        Ok(Some(query))
+    }
 
-        // Walk through directory
-        for entry in WalkDir::new(&self.directory).into_iter().filter_map(|e| e.ok()) {
-            if entry.file_type().is_file() {
-                let path = entry.path();
-                
-                if !should_search_file(path, &self.extensions) || is_binary_file(path) {
-                    continue;
-                }
+    fn prepare(
+        &self,
+        patterns: &[String],
+    ) -> Result<(LineSearch, ExcludeFilter, ThreadPool), SearchError> {
+        // Per-file totals have no lines to put context around
+        let (before, after) = match self.output {
+            OutputMode::Lines => (self.before_context, self.after_context),
+            _ => (0, 0),
+        };
+        let search = LineSearch {
+            matcher: self.matcher(self.shown(patterns))?,
+            query: self.query()?,
+            encoding: self.encoding,
+            mmap: self.mmap,
+            binary: self.binary,
+            search_zip: self.search_zip,
+            search_archives: self.search_archives,
+            extractors: self.extractors.clone(),
+            extensions: self.extensions.clone(),
+            before,
+            after,
+            invert: self.invert,
+            output: self.output,
+        };
+
+        let exclude_filter = ExcludeFilter::new(&self.excludes)?;
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.threads)
+            .build()?;
+
+        Ok((search, exclude_filter, pool))
+    }
+}
 
-                files_searched += 1;
-                if files_searched % 50 == 0 {
-                    pb.set_message(format!("Searched {} files...", files_searched));
+// Everything a worker needs to search one file
+#[derive(Clone)]
+struct LineSearch {
+    matcher: Matcher,
+    query: Option<Query>,
+    encoding: Option<&'static Encoding>,
+    mmap: MmapChoice,
+    binary: BinaryMode,
+    search_zip: bool,
+    search_archives: bool,
+    extractors: Vec<Arc<dyn Extractor>>,
+    // Applied to archive members; the walker filters everything else
+    extensions: Option<HashSet<String>>,
+    before: usize,
+    after: usize,
+    invert: bool,
+    output: OutputMode,
+}
+
+impl LineSearch {
+    // Returns the bytes searched, or None if the file was skipped as binary.
+    // Archive members searched are added to `members` with their own bytes.
+    fn file(
+        &self,
+        path: &Path,
+        events: &mut Vec<LineEvent>,
+        members: &mut Vec<(PathBuf, u64)>,
+    ) -> io::Result<Option<u64>> {
+        let mut file = fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        if let Some(extractor) = self.extractors.iter().find(|e| e.handles(path)) {
+            let segments = extractor.extract(path)?;
+            self.document(path, &segments, events);
+            return Ok(Some(len));
+        }
+        if let Some(format) = ArchiveFormat::of(path).filter(|_| self.search_archives) {
+            archive::read_files(path, format, |member, reader| {
+                if !should_search_file(member, &self.extensions) {
+                    return Ok(());
                 }
+                let mut counted = Counted {
+                    inner: reader,
+                    bytes: 0,
+                };
+                let reader = match Compression::of(member).filter(|_| self.search_zip) {
+                    Some(compression) => decompress::reader(&mut counted, compression)?,
+                    None => Box::new(&mut counted),
+                };
+                if self.read_once(member, reader, events)? {
+                    members.push((member.to_path_buf(), counted.bytes));
+                }
+                Ok(())
+            })?;
+            return Ok(Some(len));
+        }
+        if let Some(compression) = Compression::of(path).filter(|_| self.search_zip) {
+            let reader = decompress::reader(file, compression)?;
+            let searched = self.read_once(path, reader, events)?;
+            return Ok(searched.then_some(len));
+        }
+        let binary = match self.binary {
+            BinaryMode::Text => false,
+            _ => self.looks_binary(&read_sample(&mut file)?),
+        };
+        if binary && self.binary == BinaryMode::Skip {
+            return Ok(None);
+        }
+        let map = match self.mmap {
+            MmapChoice::Auto => len >= MMAP_THRESHOLD,
+            MmapChoice::Always => len > 0,
+            MmapChoice::Never => false,
+        };
 
-                if let Ok(file_matches) = self.search_in_file(path, &search_regex) {
-                    all_matches.extend(file_matches);
+        if binary {
+            let found = if map {
+                // SAFETY: as below
+                self.binary_offsets(path, &unsafe { Mmap::map(&file)? })
+            } else {
+                let mut bytes = Vec::with_capacity(len as usize);
+                file.read_to_end(&mut bytes)?;
+                self.binary_offsets(path, &bytes)
+            };
+            events.extend(found.map(LineEvent::Binary));
+            return Ok(Some(len));
+        }
+
+        // Files that need transcoding go through the decoder either way
+        if map && self.encoding.is_none() {
+            // SAFETY: the map is read-only and dropped before returning. If
+            // another process truncates the file meanwhile, reading the
+            // missing pages can fault, as with any mmap-based search.
+            let mapped = unsafe { Mmap::map(&file)? };
+            if !has_utf16_bom(&mapped) {
+                let buffer = mapped.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&mapped);
+                self.buffer(path, buffer, events);
+                return Ok(Some(len));
+            }
+        }
+        self.stream(path, decode::reader(file, self.encoding), events)?;
+        Ok(Some(len))
+    }
+
+    // Like `file` for decompressed files and archive members, which can only
+    // be read once: the binary check samples the stream and then puts the
+    // sample back. Returns false if it was skipped as binary.
+    fn read_once(
+        &self,
+        path: &Path,
+        mut reader: impl Read,
+        events: &mut Vec<LineEvent>,
+    ) -> io::Result<bool> {
+        let mut sample = Vec::with_capacity(BINARY_SAMPLE_SIZE);
+        reader
+            .by_ref()
+            .take(BINARY_SAMPLE_SIZE as u64)
+            .read_to_end(&mut sample)?;
+        let binary = self.binary != BinaryMode::Text && self.looks_binary(&sample);
+        match self.binary {
+            BinaryMode::Skip if binary => return Ok(false),
+            BinaryMode::Search if binary => {
+                reader.read_to_end(&mut sample)?;
+                events.extend(self.binary_offsets(path, &sample).map(LineEvent::Binary));
+            }
+            _ => {
+                let content = io::Cursor::new(sample).chain(reader);
+                self.stream(path, decode::reader(content, self.encoding), events)?;
+            }
+        }
+        Ok(true)
+    }
+
+    // Search extracted text as one buffer, then label each match with the
+    // segment its line came from
+    fn document(&self, path: &Path, segments: &[Segment], events: &mut Vec<LineEvent>) {
+        let mut text = String::new();
+        // The first line of each segment and its location
+        let mut starts = Vec::new();
+        let mut line_number = 1;
+        for segment in segments.iter().filter(|s| !s.text.is_empty()) {
+            starts.push((line_number, segment.location.as_deref()));
+            let start = text.len();
+            text.push_str(&segment.text);
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+            line_number += text[start..].matches('\n').count();
+        }
+
+        let first = events.len();
+        self.buffer(path, text.as_bytes(), events);
+        for event in &mut events[first..] {
+            if let LineEvent::Match(m) = event {
+                let segment = starts.partition_point(|(start, _)| *start <= m.line_number);
+                m.location = starts[segment - 1].1.map(str::to_string);
+            }
+        }
+    }
+
+    // With -l and -L, one selected line settles a file, unless a query
+    // still has to see the rest of it
+    fn stops_at_first(&self) -> bool {
+        let per_file = matches!(
+            self.output,
+            OutputMode::FilesWithMatches | OutputMode::FilesWithoutMatch
+        );
+        per_file && self.query.is_none()
+    }
+
+    // With -c, -l and -L, turn a file's events into one count per file.
+    // Archive members are counted separately, but -L can only say that no
+    // member of an archive matched.
+    fn summarize(&self, path: &Path, events: Vec<LineEvent>, searched: bool) -> Vec<LineEvent> {
+        if self.output == OutputMode::Lines {
+            return events;
+        }
+        let mut counts: BTreeMap<PathBuf, usize> = BTreeMap::new();
+        let mut last_line = None;
+        for event in events {
+            match event {
+                // Several matches on a line select it once
+                LineEvent::Match(m) => {
+                    let line = (m.file_path, m.line_number);
+                    if last_line.as_ref() != Some(&line) {
+                        *counts.entry(line.0.clone()).or_default() += 1;
+                        last_line = Some(line);
+                    }
+                }
+                LineEvent::Binary(binary) => {
+                    *counts.entry(binary.file_path).or_default() += binary.count;
                 }
+                LineEvent::Context(_) | LineEvent::Count(_) => {}
             }
         }
 
-        pb.finish_and_clear();
+        if self.output == OutputMode::FilesWithoutMatch {
+            if !counts.is_empty() || !searched {
+                return Vec::new();
+            }
+            return vec![LineEvent::Count(FileCount {
+                file_path: path.to_path_buf(),
+                count: 0,
+            })];
+        }
+        counts
+            .into_iter()
+            .map(|(file_path, count)| LineEvent::Count(FileCount { file_path, count }))
+            .collect()
+    }
 
-        // Display results
-        self.display_results(&all_matches, pattern, files_searched);
+    fn looks_binary(&self, sample: &[u8]) -> bool {
+        // UTF-16 text is full of NULs, so only magic numbers give it away
+        match self.encoding {
+            Some(encoding)
+                if encoding == encoding_rs::UTF_16LE || encoding == encoding_rs::UTF_16BE =>
+            {
+                has_binary_magic(sample)
+            }
+            _ => looks_binary(sample),
+        }
     }
 
-    fn search_in_file(&self, path: &Path, regex: &Regex) -> Result<Vec<Match>, std::io::Error> {
-        let file = fs::File::open(path)?;
-        let reader = BufReader::new(file);
-        let mut matches = Vec::new();
+    // Binary files have no meaningful lines, so only match offsets are kept
+    fn binary_offsets(&self, path: &Path, bytes: &[u8]) -> Option<BinaryMatch> {
+        if let Some(query) = &self.query {
+            let mut seen = query.unseen();
+            query.mark(bytes, &mut seen);
+            if !query.passes(&seen) {
+                return None;
+            }
+        }
+        let mut offsets = Vec::new();
+        let mut count = 0;
+        let mut pos = 0;
+        while let Some(found) = self.matcher.find_buffer(bytes, pos) {
+            if offsets.len() < MAX_BINARY_OFFSETS {
+                offsets.push(found.start as u64);
+            }
+            count += 1;
+            if self.stops_at_first() || found.end == bytes.len() {
+                break;
+            }
+            // Step past an empty match so it isn't found again
+            pos = found.end + usize::from(found.is_empty());
+        }
+        (count > 0).then(|| BinaryMatch {
+            file_path: path.to_path_buf(),
+            offsets,
+            count,
+        })
+    }
 
-        for (line_number, line_result) in reader.lines().enumerate() {
-            let line = line_result?;
-            
-            for mat in regex.find_iter(&line) {
-                matches.push(Match {
-                    file_path: path.to_path_buf(),
-                    line_number: line_number + 1,
-                    line_content: line.clone(),
-                    match_start: mat.start(),
-                    match_end: mat.end(),
-                });
+    fn stream(
+        &self,
+        path: &Path,
+        mut reader: impl BufRead,
+        events: &mut Vec<LineEvent>,
+    ) -> io::Result<()> {
+        let (before, after) = (self.before, self.after);
+        let mut line = Vec::new();
+        let mut line_number = 0;
+        let mut offset = 0u64;
+
+        // Lines that may become before-context, and how many after-context
+        // lines are still owed to the last match
+        let mut pending: VecDeque<ContextLine> = VecDeque::with_capacity(before);
+        let mut after_remaining = 0;
+        // With a query, nothing found in a file counts until it passes
+        let first = events.len();
+        let mut seen = self.query.as_ref().map(Query::unseen);
+
+        loop {
+            line.clear();
+            let read = match reader.read_until(b'\n', &mut line) {
+                Ok(read) => read,
+                Err(e) => {
+                    if self.query.is_some() {
+                        events.truncate(first);
+                    }
+                    let message = format!("line {}: {}", line_number + 1, e);
+                    return Err(io::Error::new(e.kind(), message));
+                }
+            };
+            if read == 0 {
+                break;
+            }
+            line_number += 1;
+            let content = trim_line_end(&line);
+            if let (Some(query), Some(seen)) = (&self.query, seen.as_mut()) {
+                query.mark(content, seen);
+                if query.failed(seen) {
+                    events.truncate(first);
+                    return Ok(());
+                }
             }
+
+            let found = self.line_matches(path, line_number, offset, content);
+            if !found.is_empty() {
+                events.extend(pending.drain(..).map(LineEvent::Context));
+                events.extend(found.into_iter().map(LineEvent::Match));
+                if self.stops_at_first() {
+                    break;
+                }
+                after_remaining = after;
+            } else if after_remaining > 0 || before > 0 {
+                let context = context_line(path, line_number, offset, content);
+                if after_remaining > 0 {
+                    events.push(LineEvent::Context(context));
+                    after_remaining -= 1;
+                } else {
+                    if pending.len() == before {
+                        pending.pop_front();
+                    }
+                    pending.push_back(context);
+                }
+            }
+            offset += read as u64;
         }
 
-        Ok(matches)
+        if let (Some(query), Some(seen)) = (&self.query, &seen) {
+            if !query.passes(seen) {
+                events.truncate(first);
+            }
+        }
+        Ok(())
     }
 
-    fn display_results(&self, matches: &[Match], pattern: &str, files_searched: usize) {
-        if matches.is_empty() {
-            println!(
-                "{} No matches found for pattern: {} (searched {} files)",
-                "😔".bright_red(),
-                pattern.bright_white().bold(),
-                files_searched.to_string().bright_yellow()
-            );
+    // Search a whole file at once. Only lines around a hit are split out and
+    // numbered; line numbers come from counting newlines between hits.
+    fn buffer(&self, path: &Path, buffer: &[u8], events: &mut Vec<LineEvent>) {
+        // Hits say nothing about the lines between them, so every line has
+        // to be looked at. Reading from memory can't fail.
+        if self.invert || self.query.is_some() {
+            let _ = self.stream(path, buffer, events);
             return;
         }
+        // Line number of the line starting at `counted`
+        let (mut counted, mut line_number) = (0, 1);
+        // Everything before `printed` has been emitted, or can't be context
+        let (mut printed, mut printed_line) = (0, 1);
+        let mut after_remaining = 0;
+        let mut pos = 0;
+
+        while let Some(candidate) = self.matcher.find_buffer(buffer, pos) {
+            let start = buffer[..candidate.start]
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .map_or(0, |i| i + 1);
+            if start == buffer.len() {
+                // An empty match after the final newline isn't on any line
+                break;
+            }
+            let end = buffer[candidate.start..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(buffer.len(), |i| candidate.start + i + 1);
+            line_number += count_lines(&buffer[counted..start]);
+            counted = start;
+            pos = end;
+
+            // The candidate may have spanned lines; only in-line matches count
+            let content = trim_line_end(&buffer[start..end]);
+            let found = self.line_matches(path, line_number, start as u64, content);
+            if found.is_empty() {
+                continue;
+            }
+
+            // After-context owed to the previous hit, then before-context
+            // that doesn't overlap it
+            let mut from = printed;
+            for number in printed_line..printed_line + after_remaining {
+                if from >= start {
+                    break;
+                }
+                from = self.push_context(path, buffer, from, number, events);
+            }
+            let mut before_start = start;
+            let mut before_count = 0;
+            while before_count < self.before && before_start > from {
+                before_start = buffer[..before_start - 1]
+                    .iter()
+                    .rposition(|&b| b == b'\n')
+                    .map_or(0, |i| i + 1);
+                before_count += 1;
+            }
+            let mut line_start = before_start.max(from);
+            for number in line_number - before_count..line_number {
+                line_start = self.push_context(path, buffer, line_start, number, events);
+            }
 
-        // Group matches by file
-        let mut file_matches: std::collections::HashMap<&Path, Vec<&Match>> =
-            std::collections::HashMap::new();
-        for m in matches {
-            file_matches.entry(&m.file_path).or_default().push(m);
+            events.extend(found.into_iter().map(LineEvent::Match));
+            if self.stops_at_first() {
+                return;
+            }
+            printed = end;
+            printed_line = line_number + 1;
+            after_remaining = self.after;
+            if end == buffer.len() {
+                break;
+            }
         }
 
-        println!(
-            "{} Found {} match{} in {} file{} (searched {} files):",
-            "🎉".bright_green(),
-            matches.len().to_string().bright_yellow().bold(),
-            if matches.len() == 1 { "" } else { "es" },
-            file_matches.len().to_string().bright_cyan().bold(),
-            if file_matches.len() == 1 { "" } else { "s" },
-            files_searched.to_string().bright_white()
-        );
-        println!("{}", "═".repeat(80).bright_blue());
-
-        let mut file_index = 1;
-        for (file_path, file_match_list) in &file_matches {
-            self.display_file_matches(file_path, file_match_list, file_index);
-            file_index += 1;
-            
-            if file_index <= file_matches.len() {
-                println!("{}", "─".repeat(80).bright_black());
+        let mut from = printed;
+        for number in printed_line..printed_line + after_remaining {
+            if from >= buffer.len() {
+                break;
             }
+            from = self.push_context(path, buffer, from, number, events);
         }
+    }
 
-        println!("{}", "═".repeat(80).bright_blue());
-        println!(
-            "{} Search completed. Found {} match{} in {} file{}.",
-            "✅".bright_green(),
-            matches.len().to_string().bright_yellow().bold(),
-            if matches.len() == 1 { "" } else { "es" },
-            file_matches.len().to_string().bright_cyan().bold(),
-            if file_matches.len() == 1 { "" } else { "s" }
-        );
+    // Emit the line starting at `start` as context and return where the next
+    // one starts
+    fn push_context(
+        &self,
+        path: &Path,
+        buffer: &[u8],
+        start: usize,
+        line_number: usize,
+        events: &mut Vec<LineEvent>,
+    ) -> usize {
+        let end = buffer[start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(buffer.len(), |i| start + i + 1);
+        let content = trim_line_end(&buffer[start..end]);
+        events.push(LineEvent::Context(context_line(
+            path,
+            line_number,
+            start as u64,
+            content,
+        )));
+        end
     }
 
-    fn display_file_matches(&self, file_path: &Path, matches: &[&Match], file_index: usize) {
-        let filename = file_path.file_name().unwrap().to_string_lossy();
-        let directory = file_path.parent().unwrap_or(Path::new("")).display();
+    fn line_matches(
+        &self,
+        path: &Path,
+        line_number: usize,
+        offset: u64,
+        content: &[u8],
+    ) -> Vec<Match> {
+        let found = self.matcher.find_line(content);
+        if self.invert {
+            // A selected line has nothing in it to highlight
+            return match found.first() {
+                Some(_) => Vec::new(),
+                None => vec![Match {
+                    file_path: path.to_path_buf(),
+                    line_number,
+                    byte_offset: offset,
+                    line_content: String::from_utf8_lossy(content).into_owned(),
+                    match_start: 0,
+                    match_end: 0,
+                    raw_line: raw_line(content),
+                    raw_start: 0,
+                    raw_end: 0,
+                    location: None,
+                    pattern: 0,
+                }],
+            };
+        }
+        if found.is_empty() {
+            return Vec::new();
+        }
+        let text = String::from_utf8_lossy(content);
+        found
+            .into_iter()
+            .map(|mat| {
+                // Replacement characters can shift offsets, so measure the
+                // match in the lossy text
+                let match_start = String::from_utf8_lossy(&content[..mat.start]).len();
+                let match_end =
+                    match_start + String::from_utf8_lossy(&content[mat.start..mat.end]).len();
+                Match {
+                    file_path: path.to_path_buf(),
+                    line_number,
+                    byte_offset: offset,
+                    line_content: text.to_string(),
+                    match_start,
+                    match_end,
+                    raw_line: raw_line(content),
+                    raw_start: mat.start,
+                    raw_end: mat.end,
+                    location: None,
+                    pattern: mat.pattern,
+                }
+            })
+            .collect()
+    }
+}
+
+fn context_line(path: &Path, line_number: usize, offset: u64, content: &[u8]) -> ContextLine {
+    ContextLine {
+        file_path: path.to_path_buf(),
+        line_number,
+        byte_offset: offset,
+        line_content: String::from_utf8_lossy(content).into_owned(),
+        raw_line: raw_line(content),
+    }
+}
+
+// Lines that aren't valid UTF-8 keep their bytes alongside the lossy text
+fn raw_line(content: &[u8]) -> Option<Vec<u8>> {
+    std::str::from_utf8(content)
+        .is_err()
+        .then(|| content.to_vec())
+}
+
+// A pattern list short enough for a header or summary line
+fn describe(patterns: &[String]) -> String {
+    const SHOWN: usize = 3;
+    match patterns.len() {
+        0..=SHOWN => patterns.join(", "),
+        n => format!("{} and {} more", patterns[..SHOWN].join(", "), n - SHOWN),
+    }
+}
 
-        println!(
-            "{} {}. {} {} ({} match{})",
-            "📄".bright_blue(),
-            file_index.to_string().bright_white().bold(),
-            get_file_icon(file_path),
-            filename.bright_white().bold(),
-            matches.len().to_string().bright_yellow(),
-            if matches.len() == 1 { "" } else { "es" }
+fn count_lines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b == b'\n').count()
+}
+
+pub(crate) fn trim_line_end(line: &[u8]) -> &[u8] {
+    let end = line
+        .iter()
+        .rposition(|&b| b != b'\n' && b != b'\r')
+        .map_or(0, |i| i + 1);
+    &line[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_returns_report() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "hello world\nnothing here\nsay hello\n").unwrap();
+        fs::write(dir.path().join("b.rs"), "fn hello() {}\n").unwrap();
+
+        let extensions = "txt".to_string();
+        let searcher = GrepSearcher::new(
+            dir.path().to_str().unwrap(),
+            false,
+            false,
+            Some(&extensions),
+        )
+        .with_threads(2);
+        let report = searcher.search("hello").unwrap();
+
+        assert_eq!(report.files_searched, 1);
+        assert_eq!(report.matches.len(), 2);
+        assert_eq!(report.matches[0].line_number, 1);
+        assert_eq!(report.matches[1].line_number, 3);
+        assert_eq!(report.matches[1].byte_offset, 25);
+        assert_eq!(report.matches[1].match_start, 4);
+        assert_eq!(report.matches[1].match_end, 9);
+    }
+
+    #[test]
+    fn test_case_insensitive_regex() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "TODO: one\ntodo: two\n").unwrap();
+
+        let searcher = GrepSearcher::new(dir.path().to_str().unwrap(), true, true, None);
+        let report = searcher.search("to+do:").unwrap();
+        assert_eq!(report.matches.len(), 2);
+    }
+
+    #[test]
+    fn test_context_windows_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = "1\n2\nhit\n4\nhit\n6\n7\n8\n9\nhit\n";
+        fs::write(dir.path().join("a.txt"), text).unwrap();
+
+        let searcher = GrepSearcher::new(dir.path().to_str().unwrap(), false, false, None)
+            .with_context(1, 1);
+        let report = searcher.search("hit").unwrap();
+
+        let context: Vec<usize> = report.context.iter().map(|c| c.line_number).collect();
+        assert_eq!(report.matches.len(), 3);
+        assert_eq!(context, vec![2, 4, 6, 9]);
+    }
+
+    #[test]
+    fn test_ignore_files_are_respected() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("build")).unwrap();
+        fs::write(dir.path().join(".ffignore"), "build/\n").unwrap();
+        fs::write(dir.path().join("build").join("out.txt"), "needle\n").unwrap();
+        fs::write(dir.path().join(".hidden.txt"), "needle\n").unwrap();
+        fs::write(dir.path().join("src.txt"), "needle\n").unwrap();
+
+        let root = dir.path().to_str().unwrap();
+        let report = GrepSearcher::new(root, false, false, None)
+            .search("needle")
+            .unwrap();
+        assert_eq!(report.matches.len(), 1);
+
+        let everything = WalkOptions {
+            respect_ignore: false,
+            hidden: true,
+            ..WalkOptions::default()
+        };
+        let report = GrepSearcher::new(root, false, false, None)
+            .with_walk_options(everything)
+            .search("needle")
+            .unwrap();
+        assert_eq!(report.matches.len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_regex_is_an_error() {
+        let searcher = GrepSearcher::new(".", false, true, None);
+        assert!(matches!(
+            searcher.search("("),
+            Err(SearchError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_matches_stops_early() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..20 {
+            fs::write(dir.path().join(format!("{}.txt", i)), "needle\nneedle\n").unwrap();
+        }
+
+        let searcher = GrepSearcher::new(dir.path().to_str().unwrap(), false, false, None);
+        let first: Vec<Match> = searcher
+            .matches("needle")
+            .take(3)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(first.len(), 3);
+        assert_eq!(searcher.matches("needle").count(), 40);
+    }
+
+    #[test]
+    fn test_non_utf8_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mixed = dir.path().join("mixed.txt");
+        fs::write(&mixed, b"needle\nmore \xff needle\nneedle\n").unwrap();
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend(
+            "log line\r\nneedle\r\n"
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes),
         );
-        
-        println!(
-            "   {} {}",
-            "📍".bright_yellow(),
-            directory.to_string().bright_cyan()
+        fs::write(dir.path().join("windows.log"), utf16).unwrap();
+
+        let searcher = GrepSearcher::new(dir.path().to_str().unwrap(), false, false, None);
+        let report = searcher.search("needle").unwrap();
+        assert!(report.errors.is_empty());
+        assert_eq!(report.matches.len(), 4);
+
+        // The invalid byte doesn't stop the file, and the match range still
+        // lines up with the lossy text
+        let lossy = &report.matches[1];
+        assert_eq!(lossy.line_content, "more \u{FFFD} needle");
+        assert_eq!(
+            &lossy.line_content[lossy.match_start..lossy.match_end],
+            "needle"
         );
 
-        // Show matches with context
-        for (i, m) in matches.iter().enumerate() {
-            self.display_match(m, i + 1);
+        let transcoded = &report.matches[3];
+        assert_eq!(transcoded.line_number, 2);
+        assert_eq!(transcoded.line_content, "needle");
+
+        let latin1 = encoding_rs::Encoding::for_label(b"latin1");
+        fs::write(dir.path().join("legacy.txt"), b"caf\xe9\n").unwrap();
+        let report = searcher.with_encoding(latin1).search("café").unwrap();
+        assert_eq!(report.matches.len(), 1);
+    }
+
+    #[test]
+    fn test_mmap_matches_streaming() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = b"hit first\r\n2\n3\n4\nno\nhit a hit\n7\n8\n\xff hit\n10\n11\n12\n13\nlast hit";
+        fs::write(dir.path().join("a.txt"), text).unwrap();
+
+        let lines = |mmap: MmapChoice, pattern: &str, context: usize| {
+            let report = GrepSearcher::new(dir.path().to_str().unwrap(), false, true, None)
+                .with_context(context, context)
+                .with_mmap(mmap)
+                .search(pattern)
+                .unwrap();
+            let matches: Vec<_> = report
+                .matches
+                .iter()
+                .map(|m| {
+                    (
+                        m.line_number,
+                        m.byte_offset,
+                        m.match_start,
+                        m.line_content.clone(),
+                    )
+                })
+                .collect();
+            let context: Vec<_> = report
+                .context
+                .iter()
+                .map(|c| (c.line_number, c.byte_offset, c.line_content.clone()))
+                .collect();
+            (matches, context)
+        };
+
+        for pattern in ["hit", "^hit", "hit$", "^$", "\\d\\n\\d"] {
+            for context in [0, 1, 3] {
+                let streamed = lines(MmapChoice::Never, pattern, context);
+                assert_eq!(lines(MmapChoice::Always, pattern, context), streamed);
+            }
+        }
+        assert_eq!(lines(MmapChoice::Always, "hit", 0).0.len(), 5);
+    }
+
+    #[test]
+    fn test_binary_modes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("text.txt"), "needle\n").unwrap();
+        fs::write(dir.path().join("blob.bin"), b"\x7fELF\0\0needle\0needle").unwrap();
+        let search = |binary: BinaryMode| {
+            GrepSearcher::new(dir.path().to_str().unwrap(), false, false, None)
+                .with_binary(binary)
+                .search("needle")
+                .unwrap()
+        };
+
+        let report = search(BinaryMode::Skip);
+        assert_eq!(report.matches.len(), 1);
+        assert!(report.binary_matches.is_empty());
+        assert_eq!(report.files_searched, 1);
+
+        let report = search(BinaryMode::Search);
+        assert_eq!(report.matches.len(), 1);
+        assert_eq!(report.binary_matches[0].offsets, vec![6, 13]);
+        assert_eq!(report.files_searched, 2);
+
+        let report = search(BinaryMode::Text);
+        assert_eq!(report.matches.len(), 3);
+        assert!(report.binary_matches.is_empty());
+    }
+
+    #[test]
+    fn test_output_modes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "todo\ndone\ntodo todo\n").unwrap();
+        fs::write(dir.path().join("b.txt"), "done\n").unwrap();
+        let search = |invert: bool, output: OutputMode, mmap: MmapChoice| {
+            GrepSearcher::new(dir.path().to_str().unwrap(), false, false, None)
+                .with_invert_match(invert)
+                .with_output(output)
+                .with_mmap(mmap)
+                .search("todo")
+                .unwrap()
+        };
+        let counts = |report: &GrepReport| -> Vec<(String, usize)> {
+            let name = |path: &Path| path.file_name().unwrap().to_string_lossy().into_owned();
+            let files = report.file_counts.iter();
+            files.map(|f| (name(&f.file_path), f.count)).collect()
+        };
+
+        for mmap in [MmapChoice::Never, MmapChoice::Always] {
+            let report = search(true, OutputMode::Lines, mmap);
+            let lines: Vec<_> = report.matches.iter().map(|m| m.line_number).collect();
+            assert_eq!(lines, vec![2, 1]);
+            assert!(report.inverted);
+
+            // Lines, not matches, and -l stops at the first
+            let report = search(false, OutputMode::Count, mmap);
+            assert_eq!(counts(&report), vec![("a.txt".to_string(), 2)]);
+            let report = search(false, OutputMode::FilesWithMatches, mmap);
+            assert_eq!(counts(&report), vec![("a.txt".to_string(), 1)]);
+            let report = search(false, OutputMode::FilesWithoutMatch, mmap);
+            assert_eq!(counts(&report), vec![("b.txt".to_string(), 0)]);
+            assert!(report.matches.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_multiple_patterns_and_queries() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.rs"), "use foo;\nuse bar;\n").unwrap();
+        fs::write(dir.path().join("b.rs"), "use foo;\n// deprecated: bar\n").unwrap();
+        fs::write(dir.path().join("c.rs"), "use foo;\n").unwrap();
+        let searcher = |mmap: MmapChoice| {
+            GrepSearcher::new(dir.path().to_str().unwrap(), false, false, None).with_mmap(mmap)
+        };
+        let names = |report: &GrepReport| -> Vec<String> {
+            let files = report.files().into_keys();
+            files
+                .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        for mmap in [MmapChoice::Never, MmapChoice::Always] {
+            let patterns = ["bar".to_string(), "foo".to_string()];
+            let report = searcher(mmap).search_any(&patterns).unwrap();
+            assert_eq!(report.matches.len(), 5);
+            assert_eq!(report.matches[0].pattern, 1);
+            assert_eq!(report.matches[1].pattern, 0);
+
+            // Without other patterns, the --all-of ones are shown
+            let report = searcher(mmap)
+                .with_all_of(patterns.to_vec())
+                .with_none_of(vec!["deprecated".to_string()])
+                .search_any(&[])
+                .unwrap();
+            assert_eq!(names(&report), vec!["a.rs"]);
+            assert_eq!(report.patterns, patterns);
+
+            let report = searcher(mmap)
+                .with_none_of(vec!["bar".to_string()])
+                .search("use")
+                .unwrap();
+            assert_eq!(names(&report), vec!["c.rs"]);
         }
     }
 
-    fn display_match(&self, m: &Match, match_index: usize) {
-        let line_num_str = format!("{:4}", m.line_number);
-        
-        // Highlight the matched text
-        let before_match = &m.line_content[..m.match_start];
-        let matched_text = &m.line_content[m.match_start..m.match_end];
-        let after_match = &m.line_content[m.match_end..];
+    #[test]
+    fn test_several_patterns_ignore_case() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "USE foo;\nCafé\n").unwrap();
+        fs::write(dir.path().join("b.txt"), "use BAR;\nDEPRECATED\n").unwrap();
+        let searcher =
+            |regex: bool| GrepSearcher::new(dir.path().to_str().unwrap(), true, regex, None);
+
+        let patterns = ["us+e".to_string(), "ba.".to_string()];
+        let report = searcher(true).search_any(&patterns).unwrap();
+        assert_eq!(report.matches.len(), 3);
+
+        let report = searcher(true)
+            .with_all_of(vec!["u[s]e".to_string()])
+            .with_none_of(vec!["dep.*ted".to_string()])
+            .search_any(&[])
+            .unwrap();
+        assert_eq!(report.matches.len(), 1);
+        assert!(report.matches[0].file_path.ends_with("a.txt"));
+
+        // A non-ASCII literal folds Unicode case, like a single pattern does
+        let patterns = ["É".to_string(), "x".to_string()];
+        let report = searcher(false).search_any(&patterns).unwrap();
+        assert_eq!(report.matches.len(), 1);
+        assert_eq!(report.matches[0].line_number, 2);
+    }
+
+    #[test]
+    fn test_search_zip() {
+        use std::io::Write;
 
-        println!(
-            "     {} {} │ {}{}{}",
-            match_index.to_string().bright_magenta(),
-            line_num_str.bright_blue(),
-            before_match.trim_start(),
-            matched_text.on_bright_yellow().black().bold(),
-            after_match
+        let dir = tempfile::tempdir().unwrap();
+        let text = b"start\nneedle one\nmiddle\nneedle two\n";
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(text).unwrap();
+        fs::write(dir.path().join("app.log.1.gz"), gz.finish().unwrap()).unwrap();
+        let mut bz = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bz.write_all(text).unwrap();
+        fs::write(dir.path().join("app.log.2.bz2"), bz.finish().unwrap()).unwrap();
+        let mut xz = liblzma::write::XzEncoder::new(Vec::new(), 6);
+        xz.write_all(text).unwrap();
+        fs::write(dir.path().join("app.log.3.xz"), xz.finish().unwrap()).unwrap();
+        let zst = zstd::encode_all(&text[..], 0).unwrap();
+        fs::write(dir.path().join("app.log.4.zst"), zst).unwrap();
+
+        let searcher = || GrepSearcher::new(dir.path().to_str().unwrap(), false, false, None);
+        let report = searcher().search("needle").unwrap();
+        assert!(report.matches.is_empty());
+
+        let report = searcher().with_search_zip(true).search("needle").unwrap();
+        assert_eq!(report.files().len(), 4);
+        assert!(report.errors.is_empty());
+        for found in report.files().values() {
+            let lines: Vec<_> = found.iter().map(|m| m.line_number).collect();
+            assert_eq!(lines, vec![2, 4]);
+        }
+    }
+
+    #[test]
+    fn test_search_archives() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("vendor.jar");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&bundle).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("src/lib.rs", options).unwrap();
+        zip.write_all(b"// nothing\nlet needle = 1;\n").unwrap();
+        zip.start_file("notes.txt", options).unwrap();
+        zip.write_all(b"needle\n").unwrap();
+        zip.start_file("logs/old.log.gz", options).unwrap();
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(b"needle\n").unwrap();
+        zip.write_all(&gz.finish().unwrap()).unwrap();
+        zip.finish().unwrap();
+
+        let searcher = |extensions: Option<&String>| {
+            GrepSearcher::new(dir.path().to_str().unwrap(), false, false, extensions)
+                .with_search_archives(true)
+        };
+        let report = searcher(None).search("needle").unwrap();
+        let files: Vec<_> = report.files().into_keys().collect();
+        assert_eq!(
+            files,
+            vec![
+                archive::member_path(&bundle, "notes.txt"),
+                archive::member_path(&bundle, "src/lib.rs"),
+            ]
         );
+        assert_eq!(report.matches[1].line_number, 2);
+        // Members count their own bytes, not the archive's
+        let bytes: Vec<u64> = report.file_bytes.values().copied().collect();
+        assert_eq!(bytes, vec![7, 27]);
+
+        let report = searcher(None)
+            .with_search_zip(true)
+            .search("needle")
+            .unwrap();
+        assert_eq!(report.files().len(), 3);
+
+        let report = searcher(Some(&"rs".to_string())).search("needle").unwrap();
+        assert_eq!(report.matches.len(), 1);
     }
 }
@@ -0,0 +1,303 @@
+use serde_json::{json, Value};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::file_search::{FileMatch, FileReport};
+use crate::filter::EntryType;
+use crate::grep_search::{ContextLine, GrepReport, Match};
+use crate::render::print_symlink_loops;
+use crate::utils::{format_file_size, format_modified_time};
+
+// JSON Lines output, one event per line. Grep events follow ripgrep's
+// `--json` schema: a begin/match/context.../end block per matching file,
+// then a single summary.
+pub struct JsonRenderer;
+
+impl JsonRenderer {
+    pub fn print_grep_report(&self, report: &GrepReport) -> io::Result<()> {
+        print_symlink_loops(&report.symlink_loops);
+        let mut out = io::stdout().lock();
+        let mut totals = Stats::default();
+        let file_context = report.context_by_file();
+
+        for (path, matches) in report.files() {
+            let mut stats = Stats {
+                searches: 1,
+                searches_with_match: 1,
+                bytes_searched: report.file_bytes.get(path).copied().unwrap_or(0),
+                ..Stats::default()
+            };
+
+            stats.bytes_printed +=
+                write_event(&mut out, "begin", json!({ "path": path_json(path) }))?;
+            let mut context = file_context.get(path).into_iter().flatten().peekable();
+            for line in matches.chunk_by(|a, b| a.line_number == b.line_number) {
+                while let Some(c) = context.next_if(|c| c.line_number < line[0].line_number) {
+                    stats.bytes_printed += write_event(&mut out, "context", context_data(c))?;
+                }
+                stats.matched_lines += 1;
+                // An inverted line is selected for what isn't in it
+                let submatches = if report.inverted { &[][..] } else { line };
+                stats.matches += submatches.len() as u64;
+                stats.bytes_printed +=
+                    write_event(&mut out, "match", match_data(path, line[0], submatches))?;
+            }
+            for c in context {
+                stats.bytes_printed += write_event(&mut out, "context", context_data(c))?;
+            }
+            let end = json!({
+                "path": path_json(path),
+                "binary_offset": null,
+                "stats": stats.to_json(Duration::ZERO),
+            });
+            stats.bytes_printed += write_event(&mut out, "end", end)?;
+            totals.add(&stats);
+        }
+        // Binary files have no lines to report, only where matching starts
+        for binary in &report.binary_matches {
+            let path = binary.file_path.as_path();
+            let mut stats = Stats {
+                searches: 1,
+                searches_with_match: 1,
+                bytes_searched: report.file_bytes.get(path).copied().unwrap_or(0),
+                matches: binary.count as u64,
+                ..Stats::default()
+            };
+            stats.bytes_printed +=
+                write_event(&mut out, "begin", json!({ "path": path_json(path) }))?;
+            let end = json!({
+                "path": path_json(path),
+                "binary_offset": binary.offsets.first(),
+                "stats": stats.to_json(Duration::ZERO),
+            });
+            stats.bytes_printed += write_event(&mut out, "end", end)?;
+            totals.add(&stats);
+        }
+
+        totals.searches = report.files_searched as u64;
+        totals.bytes_searched = report.bytes_searched;
+        let summary = json!({
+            "elapsed_total": elapsed_json(report.elapsed),
+            "stats": totals.to_json(report.elapsed),
+            "errors": report.errors.len(),
+        });
+        write_event(&mut out, "summary", summary)?;
+        out.flush()
+    }
+
+    pub fn print_file_report(&self, report: &FileReport, directory: &Path) -> io::Result<()> {
+        print_symlink_loops(&report.symlink_loops);
+        let mut out = io::stdout().lock();
+
+        write_event(&mut out, "begin", json!({ "path": path_json(directory) }))?;
+        for file in &report.files {
+            write_event(&mut out, "match", file_data(file))?;
+        }
+        let stats = json!({
+            "elapsed": elapsed_json(report.elapsed),
+            "entries_scanned": report.entries_scanned,
+            "matches": report.files.len(),
+            "errors": report.errors.len(),
+        });
+        write_event(
+            &mut out,
+            "end",
+            json!({ "path": path_json(directory), "stats": stats }),
+        )?;
+        write_event(
+            &mut out,
+            "summary",
+            json!({ "elapsed_total": elapsed_json(report.elapsed), "stats": stats }),
+        )?;
+        out.flush()
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    searches: u64,
+    searches_with_match: u64,
+    bytes_searched: u64,
+    bytes_printed: u64,
+    matched_lines: u64,
+    matches: u64,
+}
+
+impl Stats {
+    fn add(&mut self, other: &Stats) {
+        self.searches_with_match += other.searches_with_match;
+        self.bytes_printed += other.bytes_printed;
+        self.matched_lines += other.matched_lines;
+        self.matches += other.matches;
+    }
+
+    fn to_json(&self, elapsed: Duration) -> Value {
+        json!({
+            "elapsed": elapsed_json(elapsed),
+            "searches": self.searches,
+            "searches_with_match": self.searches_with_match,
+            "bytes_searched": self.bytes_searched,
+            "bytes_printed": self.bytes_printed,
+            "matched_lines": self.matched_lines,
+            "matches": self.matches,
+        })
+    }
+}
+
+fn write_event(out: &mut impl Write, kind: &str, data: Value) -> io::Result<u64> {
+    let line = json!({ "type": kind, "data": data }).to_string();
+    writeln!(out, "{}", line)?;
+    Ok(line.len() as u64 + 1)
+}
+
+fn path_json(path: &Path) -> Value {
+    json!({ "text": path.to_string_lossy() })
+}
+
+fn elapsed_json(elapsed: Duration) -> Value {
+    json!({
+        "secs": elapsed.as_secs(),
+        "nanos": elapsed.subsec_nanos(),
+        "human": format!("{:.6}s", elapsed.as_secs_f64()),
+    })
+}
+
+// Text where the bytes are valid UTF-8, base64 otherwise, as ripgrep does
+fn data_json(bytes: &[u8]) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => json!({ "text": text }),
+        Err(_) => json!({ "bytes": base64(bytes) }),
+    }
+}
+
+fn line_json(line: &[u8]) -> Value {
+    data_json(&[line, b"\n"].concat())
+}
+
+// Standard alphabet, padded
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | ((b as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+// All matches on one line become a single event with several submatches.
+// Offsets are into the line's bytes as searched, not the lossy text.
+fn match_data(path: &Path, first: &Match, found: &[&Match]) -> Value {
+    let submatches: Vec<Value> = found
+        .iter()
+        .map(|m| {
+            json!({
+                "match": data_json(&m.line_bytes()[m.raw_start..m.raw_end]),
+                "start": m.raw_start,
+                "end": m.raw_end,
+            })
+        })
+        .collect();
+
+    let mut data = json!({
+        "path": path_json(path),
+        "lines": line_json(first.line_bytes()),
+        "line_number": first.line_number,
+        "absolute_offset": first.byte_offset,
+        "submatches": submatches,
+    });
+    if let Some(location) = &first.location {
+        data["location"] = json!(location);
+    }
+    data
+}
+
+fn context_data(line: &ContextLine) -> Value {
+    json!({
+        "path": path_json(&line.file_path),
+        "lines": line_json(line.line_bytes()),
+        "line_number": line.line_number,
+        "absolute_offset": line.byte_offset,
+        "submatches": [],
+    })
+}
+
+fn file_data(file: &FileMatch) -> Value {
+    let size = file
+        .size
+        .map(|bytes| json!({ "bytes": bytes, "human": format_file_size(bytes) }));
+    let modified = file.modified.map(|time| {
+        json!({
+            "secs": time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            "human": format_modified_time(time),
+        })
+    });
+
+    let kind = match file.kind {
+        EntryType::Directory => "dir",
+        EntryType::Symlink => "symlink",
+        _ => "file",
+    };
+    let mut data = json!({
+        "path": path_json(&file.path),
+        "type": kind,
+        "size": size,
+        "modified": modified,
+    });
+    if let Some(entries) = file.entries {
+        data["entries"] = json!(entries);
+    }
+    if let Some(target) = &file.link_target {
+        data["target"] = path_json(target);
+    }
+    if let Some(fuzzy) = &file.fuzzy {
+        data["fuzzy"] = json!({ "score": fuzzy.score, "positions": fuzzy.positions });
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GrepSearcher;
+    use std::fs;
+
+    #[test]
+    fn test_match_offsets_are_raw_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.txt");
+        fs::write(&path, b"caf\xe9 needle\n").unwrap();
+        let searcher = || GrepSearcher::new(dir.path().to_str().unwrap(), false, false, None);
+
+        // Not UTF-8: the line goes out as base64, with offsets into its bytes
+        let report = searcher().search("needle").unwrap();
+        let m = &report.matches[0];
+        let data = match_data(&path, m, &[m]);
+        assert_eq!(data["lines"], json!({ "bytes": "Y2Fm6SBuZWVkbGUK" }));
+        let submatch = &data["submatches"][0];
+        assert_eq!(submatch["match"], json!({ "text": "needle" }));
+        assert_eq!(submatch["start"], json!(5));
+        assert_eq!(submatch["end"], json!(11));
+
+        // Transcoded, it's UTF-8 text, and the offsets count é as two bytes
+        let latin1 = encoding_rs::Encoding::for_label(b"latin1");
+        let report = searcher().with_encoding(latin1).search("needle").unwrap();
+        let m = &report.matches[0];
+        let data = match_data(&path, m, &[m]);
+        assert_eq!(data["lines"], json!({ "text": "café needle\n" }));
+        assert_eq!(data["submatches"][0]["start"], json!(6));
+
+        assert_eq!(base64(b"ab"), "YWI=");
+        assert_eq!(base64(b"a"), "YQ==");
+    }
+}
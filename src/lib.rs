@@ -0,0 +1,41 @@
+//! Search engine behind the `file_finder` and `ffinder` binaries.
+//!
+//! [`FileSearcher`] finds files by name and [`GrepSearcher`] searches file
+//! contents. Both return plain report structs; the types in [`render`] turn
+//! those reports into the colorful terminal output, and [`LiveSearch`] drives
+//! both from a full-screen terminal UI.
+
+pub mod archive;
+pub mod config;
+pub mod decode;
+pub mod decompress;
+pub mod error;
+pub mod extract;
+pub mod file_search;
+pub mod filter;
+pub mod fuzzy;
+pub mod grep_search;
+pub mod json;
+pub mod live;
+pub mod patterns;
+pub mod render;
+pub mod replace;
+pub mod utils;
+pub mod walk;
+
+pub use config::{ColorTheme, Config, DefaultFlags, FlagOverrides, ProgressLevel};
+pub use error::{PathError, SearchError};
+pub use extract::{Extractor, OfficeExtractor, PdfExtractor, Segment};
+pub use file_search::{FileMatch, FileReport, FileSearcher, NameSyntax};
+pub use filter::{EntryType, FileFilters, SizeFilter};
+pub use fuzzy::FuzzyMatch;
+pub use grep_search::{
+    BinaryMatch, BinaryMode, FileCount, GrepReport, GrepSearcher, Match, Matches, MmapChoice,
+    OutputMode,
+};
+pub use json::JsonRenderer;
+pub use live::{LiveMode, LiveSearch, Selection};
+pub use patterns::read_patterns;
+pub use render::{print_errors, ErrorDisplay, FileRenderer, GrepRenderer, ReplaceRenderer};
+pub use replace::{FileEdit, Hunk, ReplacePlan};
+pub use walk::{SymlinkLoop, WalkOptions};
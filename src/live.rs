@@ -0,0 +1,732 @@
+use crossbeam_channel::{Receiver, TryRecvError};
+use crossterm::cursor::Show;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, Read, Stdout};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::Poll;
+use std::thread;
+use std::time::{Duration, Instant};
+use tui::backend::CrosstermBackend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use tui::{Frame, Terminal};
+
+use crate::config::ProgressLevel;
+use crate::error::SearchError;
+use crate::file_search::{FileReport, FileSearcher};
+use crate::grep_search::{GrepSearcher, Match, Matches};
+use crate::utils::{format_file_size, parse_list, should_search_file};
+use crate::walk::WalkOptions;
+
+// Results kept per query; the rest of the search is cancelled
+const MAX_RESULTS: usize = 1000;
+// Quiet time after a keystroke before the search is re-run
+const DEBOUNCE: Duration = Duration::from_millis(150);
+// The preview pane reads at most this much of a file
+const PREVIEW_LIMIT: u64 = 1024 * 1024;
+
+type Backend = CrosstermBackend<Stdout>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiveMode {
+    // Search file contents with GrepSearcher
+    Grep,
+    // Search file names with FileSearcher
+    Find,
+}
+
+// What the user picked with Enter
+#[derive(Debug, Clone)]
+pub struct Selection {
+    pub path: PathBuf,
+    // Set for grep results
+    pub line_number: Option<usize>,
+}
+
+// Full-screen search that re-runs as you type, with a results list and a
+// preview of the selected file
+pub struct LiveSearch {
+    directory: String,
+    mode: LiveMode,
+    query: String,
+    extensions: String,
+    case_insensitive: bool,
+    use_regex: bool,
+    threads: Option<usize>,
+    excludes: Option<String>,
+    walk: WalkOptions,
+    focus: Focus,
+    hits: Vec<Hit>,
+    // Grep rows by (file, line), so several matches on one line share a row
+    rows: HashMap<(PathBuf, usize), usize>,
+    list: ListState,
+    search: Search,
+    // Set when the query or a toggle changed and the results are out of date
+    dirty_since: Option<Instant>,
+    started: Instant,
+    finished: Option<Duration>,
+    files_searched: usize,
+    truncated: bool,
+    error: Option<String>,
+    preview: Option<(PathBuf, Vec<String>)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Query,
+    Extensions,
+}
+
+enum Search {
+    Idle,
+    Grep(Matches),
+    Find(FindTask),
+}
+
+// Raw mode on the alternate screen, undone when dropped, so a panic while
+// it's held still gives the user their terminal back
+struct ScreenGuard;
+
+impl ScreenGuard {
+    fn enter() -> io::Result<Self> {
+        enable_raw_mode()?;
+        if let Err(e) = execute!(io::stdout(), EnterAlternateScreen) {
+            let _ = disable_raw_mode();
+            return Err(e);
+        }
+        Ok(ScreenGuard)
+    }
+}
+
+impl Drop for ScreenGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, Show);
+    }
+}
+
+// A file search on its own thread; dropping it stops the walk, as dropping
+// `Matches` does for grep
+struct FindTask {
+    receiver: Receiver<Result<FileReport, SearchError>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Drop for FindTask {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Hit {
+    path: PathBuf,
+    // Matched line for grep results, file size for find results
+    line: Option<HitLine>,
+    size: Option<u64>,
+}
+
+struct HitLine {
+    number: usize,
+    content: String,
+    ranges: Vec<(usize, usize)>,
+}
+
+enum Action {
+    Continue,
+    Quit,
+    Select,
+}
+
+impl LiveSearch {
+    pub fn new(
+        directory: &str,
+        case_insensitive: bool,
+        use_regex: bool,
+        extensions: Option<&String>,
+    ) -> Self {
+        Self {
+            directory: directory.to_string(),
+            mode: LiveMode::Grep,
+            query: String::new(),
+            extensions: extensions.cloned().unwrap_or_default(),
+            case_insensitive,
+            use_regex,
+            threads: None,
+            excludes: None,
+            walk: WalkOptions::default(),
+            focus: Focus::Query,
+            hits: Vec::new(),
+            rows: HashMap::new(),
+            list: ListState::default(),
+            search: Search::Idle,
+            dirty_since: None,
+            started: Instant::now(),
+            finished: None,
+            files_searched: 0,
+            truncated: false,
+            error: None,
+            preview: None,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: LiveMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_query(mut self, query: &str) -> Self {
+        self.query = query.to_string();
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    pub fn with_excludes(mut self, excludes: Option<&String>) -> Self {
+        self.excludes = excludes.cloned();
+        self
+    }
+
+    pub fn with_walk_options(mut self, walk: WalkOptions) -> Self {
+        self.walk = walk;
+        self
+    }
+
+    // Takes over the terminal until the user quits (None) or picks a result
+    pub fn run(mut self) -> io::Result<Option<Selection>> {
+        let _screen = ScreenGuard::enter()?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        self.event_loop(&mut terminal)
+    }
+
+    fn event_loop(&mut self, terminal: &mut Terminal<Backend>) -> io::Result<Option<Selection>> {
+        self.restart();
+        loop {
+            self.collect_results();
+            if self
+                .dirty_since
+                .is_some_and(|since| since.elapsed() >= DEBOUNCE)
+            {
+                self.restart();
+            }
+            terminal.draw(|f| self.draw(f))?;
+
+            if !event::poll(Duration::from_millis(50))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            match self.handle_key(key) {
+                Action::Continue => {}
+                Action::Quit => return Ok(None),
+                Action::Select => {
+                    if let Some(hit) = self.list.selected().and_then(|i| self.hits.get(i)) {
+                        return Ok(Some(Selection {
+                            path: hit.path.clone(),
+                            line_number: hit.line.as_ref().map(|line| line.number),
+                        }));
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => return Action::Quit,
+            KeyCode::Char('t') if ctrl => {
+                self.case_insensitive = !self.case_insensitive;
+                self.mark_dirty();
+            }
+            KeyCode::Char('r') if ctrl => {
+                self.use_regex = !self.use_regex;
+                self.mark_dirty();
+            }
+            KeyCode::Char('e') if ctrl => {
+                self.focus = match self.focus {
+                    Focus::Query => Focus::Extensions,
+                    Focus::Extensions => Focus::Query,
+                };
+            }
+            KeyCode::Char('u') if ctrl => {
+                self.input().clear();
+                self.mark_dirty();
+            }
+            KeyCode::Char('n') if ctrl => self.move_selection(1),
+            KeyCode::Char('p') if ctrl => self.move_selection(-1),
+            KeyCode::Char(c) if !ctrl => {
+                self.input().push(c);
+                self.mark_dirty();
+            }
+            KeyCode::Backspace => {
+                self.input().pop();
+                self.mark_dirty();
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.mode = match self.mode {
+                    LiveMode::Grep => LiveMode::Find,
+                    LiveMode::Find => LiveMode::Grep,
+                };
+                self.mark_dirty();
+            }
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Home => self.move_selection(isize::MIN),
+            KeyCode::End => self.move_selection(isize::MAX),
+            KeyCode::Enter | KeyCode::Esc if self.focus == Focus::Extensions => {
+                self.focus = Focus::Query;
+            }
+            KeyCode::Enter => return Action::Select,
+            KeyCode::Esc => return Action::Quit,
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    fn input(&mut self) -> &mut String {
+        match self.focus {
+            Focus::Query => &mut self.query,
+            Focus::Extensions => &mut self.extensions,
+        }
+    }
+
+    fn mark_dirty(&mut self) {
+        self.dirty_since = Some(Instant::now());
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.hits.is_empty() {
+            return;
+        }
+        let current = self.list.selected().unwrap_or(0) as isize;
+        let last = self.hits.len() as isize - 1;
+        let next = current.saturating_add(delta).clamp(0, last);
+        self.list.select(Some(next as usize));
+    }
+
+    // Cancel the running search (by dropping it) and start over
+    fn restart(&mut self) {
+        self.dirty_since = None;
+        self.search = Search::Idle;
+        self.hits.clear();
+        self.rows.clear();
+        self.list.select(None);
+        self.started = Instant::now();
+        self.finished = None;
+        self.files_searched = 0;
+        self.truncated = false;
+        self.error = None;
+        if self.query.is_empty() {
+            return;
+        }
+
+        let pattern = self.query.clone();
+        let extensions = Some(&self.extensions).filter(|ext| !ext.trim().is_empty());
+
+        self.search = match self.mode {
+            LiveMode::Grep => {
+                let mut searcher = GrepSearcher::new(
+                    &self.directory,
+                    self.case_insensitive,
+                    self.use_regex,
+                    extensions,
+                )
+                .with_excludes(self.excludes.as_ref())
+                .with_progress(ProgressLevel::Off)
+                .with_walk_options(self.walk);
+                if let Some(threads) = self.threads {
+                    searcher = searcher.with_threads(threads);
+                }
+                Search::Grep(searcher.matches(&pattern))
+            }
+            LiveMode::Find => {
+                let cancel = Arc::new(AtomicBool::new(false));
+                let searcher =
+                    FileSearcher::new(&self.directory, self.case_insensitive, self.use_regex)
+                        .with_progress(ProgressLevel::Off)
+                        .with_walk_options(self.walk)
+                        .with_cancel(cancel.clone());
+                let (tx, receiver) = crossbeam_channel::bounded(1);
+                thread::spawn(move || {
+                    let _ = tx.send(searcher.search(&pattern));
+                });
+                Search::Find(FindTask { receiver, cancel })
+            }
+        };
+    }
+
+    // Pull in whatever the running search has found since the last frame
+    fn collect_results(&mut self) {
+        match std::mem::replace(&mut self.search, Search::Idle) {
+            Search::Idle => {}
+            Search::Grep(mut stream) => {
+                let done = loop {
+                    if self.hits.len() >= MAX_RESULTS {
+                        self.truncated = true;
+                        break true;
+                    }
+                    match stream.poll_next() {
+                        Poll::Ready(Some(Ok(m))) => self.push_match(m),
+                        Poll::Ready(Some(Err(e))) => {
+                            self.error = Some(e.to_string());
+                            break true;
+                        }
+                        Poll::Ready(None) => break true,
+                        Poll::Pending => break false,
+                    }
+                };
+                self.files_searched = stream.files_searched();
+                if done {
+                    self.finished = Some(self.started.elapsed());
+                } else {
+                    self.search = Search::Grep(stream);
+                }
+            }
+            Search::Find(task) => match task.receiver.try_recv() {
+                Ok(Ok(report)) => {
+                    self.push_files(report);
+                    self.finished = Some(self.started.elapsed());
+                }
+                Ok(Err(e)) => {
+                    self.error = Some(e.to_string());
+                    self.finished = Some(self.started.elapsed());
+                }
+                Err(TryRecvError::Empty) => self.search = Search::Find(task),
+                Err(TryRecvError::Disconnected) => {
+                    self.finished = Some(self.started.elapsed());
+                }
+            },
+        }
+        if self.list.selected().is_none() && !self.hits.is_empty() {
+            self.list.select(Some(0));
+        }
+    }
+
+    fn push_match(&mut self, m: Match) {
+        let key = (m.file_path.clone(), m.line_number);
+        if let Some(&row) = self.rows.get(&key) {
+            if let Some(line) = self.hits[row].line.as_mut() {
+                line.ranges.push((m.match_start, m.match_end));
+            }
+            return;
+        }
+        self.rows.insert(key, self.hits.len());
+        self.hits.push(Hit {
+            path: m.file_path,
+            line: Some(HitLine {
+                number: m.line_number,
+                content: m.line_content,
+                ranges: vec![(m.match_start, m.match_end)],
+            }),
+            size: None,
+        });
+    }
+
+    fn push_files(&mut self, report: FileReport) {
+        // FileSearcher has no extension filter of its own
+        let extensions = Some(&self.extensions)
+            .filter(|ext| !ext.trim().is_empty())
+            .map(|ext| {
+                parse_list(ext)
+                    .into_iter()
+                    .map(|e| e.to_lowercase())
+                    .collect::<HashSet<String>>()
+            });
+        self.files_searched = report.entries_scanned;
+        let files = report
+            .files
+            .into_iter()
+            .filter(|file| should_search_file(&file.path, &extensions));
+        for file in files {
+            if self.hits.len() >= MAX_RESULTS {
+                self.truncated = true;
+                break;
+            }
+            self.hits.push(Hit {
+                path: file.path,
+                line: None,
+                size: file.size,
+            });
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame<Backend>) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(3),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(f.size());
+        let inputs = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(75), Constraint::Percentage(25)])
+            .split(rows[0]);
+        let body = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+            .split(rows[1]);
+
+        self.draw_inputs(f, inputs[0], inputs[1]);
+        self.draw_results(f, body[0]);
+        self.draw_preview(f, body[1]);
+        self.draw_status(f, rows[2]);
+
+        let help =
+            "Tab mode  ^T case  ^R regex  ^E extensions  ^U clear  ↑↓ select  Enter open  Esc quit";
+        f.render_widget(
+            Paragraph::new(Span::styled(help, Style::default().fg(Color::DarkGray))),
+            rows[3],
+        );
+    }
+
+    fn draw_inputs(&self, f: &mut Frame<Backend>, query_area: Rect, ext_area: Rect) {
+        let border = |focused: bool| {
+            if focused {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::Blue)
+            }
+        };
+        let flag = |on: bool, label: &'static str| {
+            if on {
+                Span::styled(label, Style::default().fg(Color::Green))
+            } else {
+                Span::styled(label, Style::default().fg(Color::DarkGray))
+            }
+        };
+
+        let mode = match self.mode {
+            LiveMode::Grep => " Grep ",
+            LiveMode::Find => " Find ",
+        };
+        let title = Spans::from(vec![
+            Span::styled(mode, Style::default().add_modifier(Modifier::BOLD)),
+            flag(self.case_insensitive, "[ignore case] "),
+            flag(self.use_regex, "[regex] "),
+        ]);
+        let query = Paragraph::new(self.query.as_str()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border(self.focus == Focus::Query))
+                .title(title),
+        );
+        f.render_widget(query, query_area);
+
+        let extensions = Paragraph::new(self.extensions.as_str()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border(self.focus == Focus::Extensions))
+                .title(" Extensions "),
+        );
+        f.render_widget(extensions, ext_area);
+
+        let (area, text) = match self.focus {
+            Focus::Query => (query_area, &self.query),
+            Focus::Extensions => (ext_area, &self.extensions),
+        };
+        let x = area.x + 1 + text.chars().count() as u16;
+        f.set_cursor(x.min(area.right().saturating_sub(2)), area.y + 1);
+    }
+
+    fn draw_results(&mut self, f: &mut Frame<Backend>, area: Rect) {
+        let items: Vec<ListItem> = self
+            .hits
+            .iter()
+            .map(|hit| {
+                let mut spans = vec![Span::styled(
+                    self.relative(&hit.path).display().to_string(),
+                    Style::default().fg(Color::Cyan),
+                )];
+                if let Some(line) = &hit.line {
+                    spans.push(Span::styled(
+                        format!(":{} ", line.number),
+                        Style::default().fg(Color::Yellow),
+                    ));
+                    let trimmed = line.content.trim_start();
+                    let shift = line.content.len() - trimmed.len();
+                    spans.extend(highlight(trimmed, &line.ranges, shift));
+                } else if let Some(size) = hit.size {
+                    spans.push(Span::styled(
+                        format!("  {}", format_file_size(size)),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+                ListItem::new(Spans::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Results ({}) ", self.hits.len())),
+            )
+            .highlight_style(Style::default().bg(Color::DarkGray))
+            .highlight_symbol("› ");
+        f.render_stateful_widget(list, area, &mut self.list);
+    }
+
+    fn draw_preview(&mut self, f: &mut Frame<Backend>, area: Rect) {
+        let block = Block::default().borders(Borders::ALL);
+        let Some(index) = self.list.selected().filter(|&i| i < self.hits.len()) else {
+            f.render_widget(block.title(" Preview "), area);
+            return;
+        };
+
+        let path = self.hits[index].path.clone();
+        if self.preview.as_ref().map(|(p, _)| p) != Some(&path) {
+            self.preview = Some((path.clone(), load_preview(&path)));
+        }
+        let lines = self
+            .preview
+            .as_ref()
+            .map(|(_, l)| l.as_slice())
+            .unwrap_or(&[]);
+        let hit = &self.hits[index];
+
+        // Keep the matched line in the middle of the pane
+        let height = area.height.saturating_sub(2) as usize;
+        let target = hit.line.as_ref().map(|line| line.number - 1);
+        let first = target.map_or(0, |t| t.saturating_sub(height / 2));
+
+        let text: Vec<Spans> = lines
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(height)
+            .map(|(i, content)| {
+                let gutter = Span::styled(
+                    format!("{:>5} ", i + 1),
+                    Style::default().fg(Color::DarkGray),
+                );
+                match &hit.line {
+                    Some(line) if Some(i) == target => {
+                        let mut spans = vec![gutter];
+                        spans.extend(highlight(&line.content, &line.ranges, 0).into_iter().map(
+                            |span| Span::styled(span.content, span.style.bg(Color::DarkGray)),
+                        ));
+                        Spans::from(spans)
+                    }
+                    _ => Spans::from(vec![gutter, Span::raw(content.replace('\t', "    "))]),
+                }
+            })
+            .collect();
+
+        let title = format!(" {} ", self.relative(&path).display());
+        f.render_widget(Paragraph::new(text).block(block.title(title)), area);
+    }
+
+    fn draw_status(&self, f: &mut Frame<Backend>, area: Rect) {
+        let status = if let Some(error) = &self.error {
+            Span::styled(error.clone(), Style::default().fg(Color::Red))
+        } else if self.query.is_empty() {
+            Span::styled(
+                format!("Type to search {}", self.directory),
+                Style::default().fg(Color::DarkGray),
+            )
+        } else {
+            let state = match self.finished {
+                Some(elapsed) if self.truncated => {
+                    format!(
+                        "stopped at {} results, {:.2}s",
+                        MAX_RESULTS,
+                        elapsed.as_secs_f64()
+                    )
+                }
+                Some(elapsed) => format!("done in {:.2}s", elapsed.as_secs_f64()),
+                None => "searching…".to_string(),
+            };
+            Span::raw(format!(
+                "{} result{} · {} files searched · {}",
+                self.hits.len(),
+                if self.hits.len() == 1 { "" } else { "s" },
+                self.files_searched,
+                state
+            ))
+        };
+        f.render_widget(Paragraph::new(status), area);
+    }
+
+    fn relative<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.directory).unwrap_or(path)
+    }
+}
+
+// Split a line into plain and highlighted spans. `shift` is how many bytes
+// were trimmed from the front of `line` after the ranges were computed.
+fn highlight(line: &str, ranges: &[(usize, usize)], shift: usize) -> Vec<Span<'static>> {
+    let matched = Style::default()
+        .fg(Color::Black)
+        .bg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    for &(start, end) in ranges {
+        let start = start.saturating_sub(shift).max(pos).min(line.len());
+        let end = end.saturating_sub(shift).max(start).min(line.len());
+        if start > pos {
+            spans.push(Span::raw(line[pos..start].replace('\t', "    ")));
+        }
+        if end > start {
+            spans.push(Span::styled(
+                line[start..end].replace('\t', "    "),
+                matched,
+            ));
+        }
+        pos = end;
+    }
+    if pos < line.len() {
+        spans.push(Span::raw(line[pos..].replace('\t', "    ")));
+    }
+    spans
+}
+
+fn load_preview(path: &Path) -> Vec<String> {
+    let mut bytes = Vec::new();
+    let read = File::open(path).and_then(|file| file.take(PREVIEW_LIMIT).read_to_end(&mut bytes));
+    match read {
+        Err(e) => vec![format!("Cannot read {}: {}", path.display(), e)],
+        Ok(_) if bytes.contains(&0) => vec!["Binary file".to_string()],
+        Ok(_) => String::from_utf8_lossy(&bytes)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_after_trim() {
+        // "    let x = foo(foo);" trimmed by four bytes
+        let spans = highlight("let x = foo(foo);", &[(12, 15), (16, 19)], 4);
+        let text: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, vec!["let x = ", "foo", "(", "foo", ");"]);
+        assert_eq!(spans[1].style.bg, Some(Color::Yellow));
+        assert_eq!(spans[2].style.bg, None);
+    }
+}
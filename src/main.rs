@@ -2,13 +2,15 @@ use clap::{Arg, ArgMatches, Command};
 use colored::*;
 use console::Term;
 use dialoguer::{theme::ColorfulTheme, Select};
-
-mod file_search;
-mod grep_search;
-mod utils;
-
-use file_search::FileSearcher;
-use grep_search::GrepSearcher;
+use encoding_rs::Encoding;
+use file_finder::{
+    decode, filter, print_errors, read_patterns, replace, BinaryMode, Config, DefaultFlags,
+    EntryType, ErrorDisplay, FileFilters, FileRenderer, FileSearcher, FlagOverrides, GrepRenderer,
+    GrepSearcher, JsonRenderer, LiveMode, LiveSearch, MmapChoice, NameSyntax, OfficeExtractor,
+    OutputMode, PathError, PdfExtractor, ProgressLevel, ReplaceRenderer, SizeFilter, WalkOptions,
+};
+use std::path::Path;
+use std::time::SystemTime;
 
 fn main() {
     let app = Command::new("File Finder & Grep Tool")
@@ -38,12 +40,164 @@ fn main() {
                         .long("ignore-case")
                         .action(clap::ArgAction::SetTrue),
                 )
+                .arg(
+                    Arg::new("case-sensitive")
+                        .help("Case sensitive search, overriding a saved ignore_case")
+                        .long("case-sensitive")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("case-insensitive"),
+                )
                 .arg(
                     Arg::new("regex")
-                        .help("Use regex pattern")
+                        .help("Match the file name against a regex")
                         .short('r')
                         .long("regex")
                         .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-regex")
+                        .help("Don't use regex, overriding a saved regex setting")
+                        .long("no-regex")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("regex"),
+                )
+                .arg(
+                    Arg::new("glob")
+                        .help("Match a glob against the file name or relative path")
+                        .short('g')
+                        .long("glob")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("regex"),
+                )
+                .arg(
+                    Arg::new("literal")
+                        .help("Match the pattern as a plain substring of the file name")
+                        .short('F')
+                        .long("literal")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with_all(["regex", "glob"]),
+                )
+                .arg(
+                    Arg::new("fuzzy")
+                        .help("Rank paths by fuzzy match against the pattern (e.g. usrctl)")
+                        .long("fuzzy")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with_all(["regex", "glob", "literal"]),
+                )
+                .arg(
+                    Arg::new("size")
+                        .help("Size filter: +10M (at least), -1k (at most) or 512 (exactly); repeat for a range")
+                        .long("size")
+                        .value_name("SIZE")
+                        .allow_hyphen_values(true)
+                        .action(clap::ArgAction::Append)
+                        .value_parser(SizeFilter::parse),
+                )
+                .arg(
+                    Arg::new("newer")
+                        .help("Modified within a duration (30min, 2h, 2d, 3w) or since a date (2025-01-01)")
+                        .long("newer")
+                        .value_name("TIME")
+                        .value_parser(filter::parse_time),
+                )
+                .arg(
+                    Arg::new("older")
+                        .help("Modified longer ago than a duration or before a date")
+                        .long("older")
+                        .value_name("TIME")
+                        .value_parser(filter::parse_time),
+                )
+                .arg(
+                    Arg::new("type")
+                        .help("Entry type: f (file), d (directory), l (symlink) or any")
+                        .long("type")
+                        .value_name("TYPE")
+                        .value_parser(EntryType::parse),
+                )
+                .arg(
+                    Arg::new("min-depth")
+                        .help("Only show entries at least NUM levels below the directory")
+                        .long("min-depth")
+                        .value_name("NUM")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("max-depth")
+                        .help("Don't descend more than NUM levels below the directory")
+                        .long("max-depth")
+                        .value_name("NUM")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("no-ignore")
+                        .help("Don't respect .gitignore, .ignore and .ffignore files")
+                        .long("no-ignore")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("ignore")
+                        .help("Respect ignore files, overriding a saved no_ignore")
+                        .long("ignore")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("no-ignore"),
+                )
+                .arg(
+                    Arg::new("hidden")
+                        .help("Search hidden files and directories")
+                        .long("hidden")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-hidden")
+                        .help("Skip hidden files and directories, overriding a saved hidden setting")
+                        .long("no-hidden")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("hidden"),
+                )
+                .arg(
+                    Arg::new("follow")
+                        .help("Follow symbolic links")
+                        .short('L')
+                        .long("follow")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-follow")
+                        .help("Don't follow symbolic links, overriding a saved follow setting")
+                        .long("no-follow")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("follow"),
+                )
+                .arg(
+                    Arg::new("broken-symlinks")
+                        .help("Only list symlinks whose target doesn't exist")
+                        .long("broken-symlinks")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("search-archives")
+                        .help("List files inside zip, jar and tar archives as archive.zip!/path")
+                        .long("search-archives")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("json")
+                        .help("Print results as JSON Lines (ripgrep --json compatible)")
+                        .long("json")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("quiet-errors")
+                        .help("Don't list unreadable paths after the results")
+                        .long("quiet-errors")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("show-errors"),
+                )
+                .arg(
+                    Arg::new("show-errors")
+                        .help("List every unreadable path, not just the first few")
+                        .long("show-errors")
+                        .action(clap::ArgAction::SetTrue),
                 ),
         )
         .subcommand(
@@ -52,9 +206,276 @@ fn main() {
                 .arg(
                     Arg::new("pattern")
                         .help("The pattern to search for")
+                        .required_unless_present_any(["regexp", "file", "all-of"])
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("regexp")
+                        .help("Another pattern to search for; repeat for more")
+                        .short('e')
+                        .long("regexp")
+                        .value_name("PATTERN")
+                        .action(clap::ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("file")
+                        .help("Read patterns from a file, one per line")
+                        .short('f')
+                        .long("file")
+                        .value_name("FILE")
+                        .action(clap::ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("all-of")
+                        .help("Only search files that contain this pattern; repeat for more")
+                        .long("all-of")
+                        .value_name("PATTERN")
+                        .action(clap::ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("none-of")
+                        .help("Only search files that don't contain this pattern; repeat for more")
+                        .long("none-of")
+                        .value_name("PATTERN")
+                        .action(clap::ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("directory")
+                        .help("Directory to search in (default: current directory)")
+                        .short('d')
+                        .long("dir")
+                        .value_name("PATH"),
+                )
+                .arg(
+                    Arg::new("case-insensitive")
+                        .help("Case insensitive search; Unicode-aware, but several plain ASCII patterns fold ASCII only")
+                        .short('i')
+                        .long("ignore-case")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("case-sensitive")
+                        .help("Case sensitive search, overriding a saved ignore_case")
+                        .long("case-sensitive")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("case-insensitive"),
+                )
+                .arg(
+                    Arg::new("regex")
+                        .help("Use regex pattern")
+                        .short('r')
+                        .long("regex")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-regex")
+                        .help("Don't use regex, overriding a saved regex setting")
+                        .long("no-regex")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("regex"),
+                )
+                .arg(
+                    Arg::new("extensions")
+                        .help("File extensions to search (e.g., rs,py,js)")
+                        .long("ext")
+                        .value_name("EXTENSIONS"),
+                )
+                .arg(
+                    Arg::new("threads")
+                        .help("Number of worker threads (default: number of CPUs)")
+                        .short('t')
+                        .long("threads")
+                        .value_name("NUM")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("exclude")
+                        .help("Directory names or globs to exclude (e.g., node_modules,*.log)")
+                        .short('x')
+                        .long("exclude")
+                        .value_name("PATTERNS"),
+                )
+                .arg(
+                    Arg::new("after-context")
+                        .help("Show NUM lines after each match")
+                        .short('A')
+                        .long("after-context")
+                        .value_name("NUM")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("before-context")
+                        .help("Show NUM lines before each match")
+                        .short('B')
+                        .long("before-context")
+                        .value_name("NUM")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("context")
+                        .help("Show NUM lines before and after each match")
+                        .short('C')
+                        .long("context")
+                        .value_name("NUM")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("invert-match")
+                        .help("Select the lines that don't match")
+                        .short('v')
+                        .long("invert-match")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("count")
+                        .help("Only print how many lines match in each file")
+                        .short('c')
+                        .long("count")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with_all(["files-with-matches", "files-without-match", "json"]),
+                )
+                .arg(
+                    Arg::new("files-with-matches")
+                        .help("Only print the paths of files with a match")
+                        .short('l')
+                        .long("files-with-matches")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with_all(["files-without-match", "json"]),
+                )
+                .arg(
+                    Arg::new("files-without-match")
+                        .help("Only print the paths of files without a match")
+                        .short('L')
+                        .long("files-without-match")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("json"),
+                )
+                .arg(
+                    Arg::new("encoding")
+                        .help("Decode files without a BOM from this encoding (e.g., latin1, shift_jis, utf-16le)")
+                        .long("encoding")
+                        .value_name("ENCODING")
+                        .value_parser(decode::parse_encoding),
+                )
+                .arg(
+                    Arg::new("search-archives")
+                        .help("Search files inside zip, jar and tar archives")
+                        .long("search-archives")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("extract")
+                        .help("Search the text of PDF, Word, Excel and PowerPoint files")
+                        .long("extract")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("search-zip")
+                        .help("Search inside .gz, .bz2, .xz and .zst files")
+                        .short('z')
+                        .long("search-zip")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("binary")
+                        .help("What to do with binary files: skip, search (report match offsets) or text")
+                        .long("binary")
+                        .value_name("MODE")
+                        .value_parser(BinaryMode::parse),
+                )
+                .arg(
+                    Arg::new("mmap")
+                        .help("Memory-map every file instead of only large ones")
+                        .long("mmap")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("no-mmap"),
+                )
+                .arg(
+                    Arg::new("no-mmap")
+                        .help("Never memory-map files; read them through a buffer")
+                        .long("no-mmap")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("progress")
+                        .help("Show the file being searched in the progress spinner")
+                        .long("progress")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-ignore")
+                        .help("Don't respect .gitignore, .ignore and .ffignore files")
+                        .long("no-ignore")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("ignore")
+                        .help("Respect ignore files, overriding a saved no_ignore")
+                        .long("ignore")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("no-ignore"),
+                )
+                .arg(
+                    Arg::new("hidden")
+                        .help("Search hidden files and directories")
+                        .long("hidden")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-hidden")
+                        .help("Skip hidden files and directories, overriding a saved hidden setting")
+                        .long("no-hidden")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("hidden"),
+                )
+                .arg(
+                    Arg::new("follow")
+                        .help("Follow symbolic links")
+                        .long("follow")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-follow")
+                        .help("Don't follow symbolic links, overriding a saved follow setting")
+                        .long("no-follow")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("follow"),
+                )
+                .arg(
+                    Arg::new("json")
+                        .help("Print results as JSON Lines (ripgrep --json compatible)")
+                        .long("json")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("quiet-errors")
+                        .help("Don't list unreadable paths after the results")
+                        .long("quiet-errors")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("show-errors"),
+                )
+                .arg(
+                    Arg::new("show-errors")
+                        .help("List every unreadable path, not just the first few")
+                        .long("show-errors")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("replace")
+                .about("Replace text patterns in files, previewing each change")
+                .arg(
+                    Arg::new("pattern")
+                        .help("The pattern to replace")
                         .required(true)
                         .index(1),
                 )
+                .arg(
+                    Arg::new("replacement")
+                        .help("The replacement text; with --regex, $1 or ${name} insert capture groups")
+                        .required(true)
+                        .index(2),
+                )
                 .arg(
                     Arg::new("directory")
                         .help("Directory to search in (default: current directory)")
@@ -69,6 +490,13 @@ fn main() {
                         .long("ignore-case")
                         .action(clap::ArgAction::SetTrue),
                 )
+                .arg(
+                    Arg::new("case-sensitive")
+                        .help("Case sensitive search, overriding a saved ignore_case")
+                        .long("case-sensitive")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("case-insensitive"),
+                )
                 .arg(
                     Arg::new("regex")
                         .help("Use regex pattern")
@@ -76,55 +504,663 @@ fn main() {
                         .long("regex")
                         .action(clap::ArgAction::SetTrue),
                 )
+                .arg(
+                    Arg::new("no-regex")
+                        .help("Don't use regex, overriding a saved regex setting")
+                        .long("no-regex")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("regex"),
+                )
                 .arg(
                     Arg::new("extensions")
                         .help("File extensions to search (e.g., rs,py,js)")
                         .short('e')
                         .long("ext")
                         .value_name("EXTENSIONS"),
+                )
+                .arg(
+                    Arg::new("threads")
+                        .help("Number of worker threads (default: number of CPUs)")
+                        .short('t')
+                        .long("threads")
+                        .value_name("NUM")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("exclude")
+                        .help("Directory names or globs to exclude (e.g., node_modules,*.log)")
+                        .short('x')
+                        .long("exclude")
+                        .value_name("PATTERNS"),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .help("Apply every change without asking")
+                        .short('y')
+                        .long("yes")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("dry-run"),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .help("Show the changes without writing anything")
+                        .long("dry-run")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("backup")
+                        .help("Keep each original file next to it as FILE.bak")
+                        .long("backup")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-ignore")
+                        .help("Don't respect .gitignore, .ignore and .ffignore files")
+                        .long("no-ignore")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("ignore")
+                        .help("Respect ignore files, overriding a saved no_ignore")
+                        .long("ignore")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("no-ignore"),
+                )
+                .arg(
+                    Arg::new("hidden")
+                        .help("Search hidden files and directories")
+                        .long("hidden")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-hidden")
+                        .help("Skip hidden files and directories, overriding a saved hidden setting")
+                        .long("no-hidden")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("hidden"),
+                )
+                .arg(
+                    Arg::new("follow")
+                        .help("Follow symbolic links")
+                        .short('L')
+                        .long("follow")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-follow")
+                        .help("Don't follow symbolic links, overriding a saved follow setting")
+                        .long("no-follow")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("follow"),
+                )
+                .arg(
+                    Arg::new("quiet-errors")
+                        .help("Don't list unreadable paths after the results")
+                        .long("quiet-errors")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("show-errors"),
+                )
+                .arg(
+                    Arg::new("show-errors")
+                        .help("List every unreadable path, not just the first few")
+                        .long("show-errors")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("tui")
+                .about("Full-screen search that updates as you type")
+                .arg(
+                    Arg::new("query")
+                        .help("Initial search query")
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("directory")
+                        .help("Directory to search in (default: current directory)")
+                        .short('d')
+                        .long("dir")
+                        .value_name("PATH"),
+                )
+                .arg(
+                    Arg::new("case-insensitive")
+                        .help("Start with case insensitive search")
+                        .short('i')
+                        .long("ignore-case")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("case-sensitive")
+                        .help("Case sensitive search, overriding a saved ignore_case")
+                        .long("case-sensitive")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("case-insensitive"),
+                )
+                .arg(
+                    Arg::new("regex")
+                        .help("Start in regex mode")
+                        .short('r')
+                        .long("regex")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-regex")
+                        .help("Don't use regex, overriding a saved regex setting")
+                        .long("no-regex")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("regex"),
+                )
+                .arg(
+                    Arg::new("extensions")
+                        .help("File extensions to search (e.g., rs,py,js)")
+                        .short('e')
+                        .long("ext")
+                        .value_name("EXTENSIONS"),
+                )
+                .arg(
+                    Arg::new("find")
+                        .help("Start by searching file names instead of contents")
+                        .long("find")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-ignore")
+                        .help("Don't respect .gitignore, .ignore and .ffignore files")
+                        .long("no-ignore")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("ignore")
+                        .help("Respect ignore files, overriding a saved no_ignore")
+                        .long("ignore")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("no-ignore"),
+                )
+                .arg(
+                    Arg::new("hidden")
+                        .help("Search hidden files and directories")
+                        .long("hidden")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-hidden")
+                        .help("Skip hidden files and directories, overriding a saved hidden setting")
+                        .long("no-hidden")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("hidden"),
+                )
+                .arg(
+                    Arg::new("follow")
+                        .help("Follow symbolic links")
+                        .short('L')
+                        .long("follow")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-follow")
+                        .help("Don't follow symbolic links, overriding a saved follow setting")
+                        .long("no-follow")
+                        .action(clap::ArgAction::SetTrue)
+                        .overrides_with("follow"),
                 ),
         );
 
     let matches = app.get_matches();
 
+    let config = Config::load();
+    config.color_theme.apply();
+
     match matches.subcommand() {
-        Some(("find", sub_matches)) => handle_file_search(sub_matches),
-        Some(("grep", sub_matches)) => handle_grep_search(sub_matches),
+        Some(("find", sub_matches)) => handle_file_search(sub_matches, &config),
+        Some(("grep", sub_matches)) => handle_grep_search(sub_matches, &config),
+        Some(("replace", sub_matches)) => handle_replace(sub_matches, &config),
+        Some(("tui", sub_matches)) => handle_live_search(sub_matches, &config),
         _ => show_interactive_menu(),
     }
 }
 
-fn handle_file_search(matches: &ArgMatches) {
+fn handle_file_search(matches: &ArgMatches, config: &Config) {
     let filename = matches.get_one::<String>("filename").unwrap();
     let directory = matches
         .get_one::<String>("directory")
         .map(|s| s.as_str())
         .unwrap_or(".");
-    let case_insensitive = matches.get_flag("case-insensitive");
-    let use_regex = matches.get_flag("regex");
+    let flags = default_flags(matches, config);
+    let case_insensitive = flags.ignore_case;
+    let syntax = if matches.get_flag("regex") {
+        NameSyntax::Regex
+    } else if matches.get_flag("glob") {
+        NameSyntax::Glob
+    } else if matches.get_flag("literal") {
+        NameSyntax::Literal
+    } else if matches.get_flag("fuzzy") {
+        NameSyntax::Fuzzy
+    } else if flags.regex {
+        NameSyntax::Regex
+    } else {
+        NameSyntax::Auto
+    };
+
+    let filters = FileFilters {
+        sizes: matches
+            .get_many::<SizeFilter>("size")
+            .map(|sizes| sizes.copied().collect())
+            .unwrap_or_default(),
+        newer: matches.get_one::<SystemTime>("newer").copied(),
+        older: matches.get_one::<SystemTime>("older").copied(),
+        entry_type: matches
+            .get_one::<EntryType>("type")
+            .copied()
+            .unwrap_or_default(),
+        min_depth: matches.get_one::<usize>("min-depth").copied(),
+        max_depth: matches.get_one::<usize>("max-depth").copied(),
+        broken_symlinks: matches.get_flag("broken-symlinks"),
+    };
+
+    let searcher = FileSearcher::new(directory, case_insensitive, false)
+        .with_syntax(syntax)
+        .with_filters(filters)
+        .with_search_archives(matches.get_flag("search-archives"))
+        .with_walk_options(walk_options(&flags));
+    if matches.get_flag("json") {
+        match searcher.search(filename) {
+            Ok(report) => {
+                let _ = JsonRenderer.print_file_report(&report, Path::new(directory));
+                print_errors(&report.errors, error_display(matches));
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+        return;
+    }
 
     println!("{}", "🔍 File Search Mode".bright_cyan().bold());
     println!("{}", "═".repeat(50).bright_blue());
 
-    let searcher = FileSearcher::new(directory, case_insensitive, use_regex);
-    searcher.search(filename);
+    run_file_search(&searcher, filename, error_display(matches));
 }
 
-fn handle_grep_search(matches: &ArgMatches) {
-    let pattern = matches.get_one::<String>("pattern").unwrap();
+fn handle_grep_search(matches: &ArgMatches, config: &Config) {
+    let values = |id: &str| -> Vec<String> {
+        matches
+            .get_many::<String>(id)
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default()
+    };
+    let mut patterns = values("pattern");
+    patterns.extend(values("regexp"));
+    for file in values("file") {
+        match read_patterns(Path::new(&file)) {
+            Ok(read) => patterns.extend(read),
+            Err(e) => {
+                eprintln!("{} {}: {}", "❌".red(), file, e);
+                std::process::exit(2);
+            }
+        }
+    }
     let directory = matches
         .get_one::<String>("directory")
         .map(|s| s.as_str())
         .unwrap_or(".");
-    let case_insensitive = matches.get_flag("case-insensitive");
-    let use_regex = matches.get_flag("regex");
-    let extensions = matches.get_one::<String>("extensions");
+    let flags = default_flags(matches, config);
+    let case_insensitive = flags.ignore_case;
+    let use_regex = flags.regex;
+    let extensions = matches
+        .get_one::<String>("extensions")
+        .or(config.defaults.extensions.as_ref());
+    let config_excludes = config.excludes.as_ref().map(|list| list.join(","));
+    let excludes = matches
+        .get_one::<String>("exclude")
+        .or(config_excludes.as_ref());
+    let progress = if matches.get_flag("progress") {
+        ProgressLevel::Detailed
+    } else {
+        config.progress
+    };
+    let context = matches
+        .get_one::<usize>("context")
+        .copied()
+        .or(config.defaults.context)
+        .unwrap_or(0);
+    let before_context = matches
+        .get_one::<usize>("before-context")
+        .copied()
+        .unwrap_or(context);
+    let after_context = matches
+        .get_one::<usize>("after-context")
+        .copied()
+        .unwrap_or(context);
+    let output = if matches.get_flag("count") {
+        OutputMode::Count
+    } else if matches.get_flag("files-with-matches") {
+        OutputMode::FilesWithMatches
+    } else if matches.get_flag("files-without-match") {
+        OutputMode::FilesWithoutMatch
+    } else {
+        OutputMode::Lines
+    };
+
+    let mut searcher = GrepSearcher::new(directory, case_insensitive, use_regex, extensions)
+        .with_excludes(excludes)
+        .with_progress(progress)
+        .with_context(before_context, after_context)
+        .with_encoding(matches.get_one::<&Encoding>("encoding").copied())
+        .with_mmap(if matches.get_flag("mmap") {
+            MmapChoice::Always
+        } else if matches.get_flag("no-mmap") {
+            MmapChoice::Never
+        } else {
+            MmapChoice::Auto
+        })
+        .with_search_zip(matches.get_flag("search-zip"))
+        .with_search_archives(matches.get_flag("search-archives"))
+        .with_binary(
+            matches
+                .get_one::<BinaryMode>("binary")
+                .copied()
+                .unwrap_or_default(),
+        )
+        .with_all_of(values("all-of"))
+        .with_none_of(values("none-of"))
+        .with_invert_match(matches.get_flag("invert-match"))
+        .with_output(output)
+        .with_walk_options(walk_options(&flags));
+    if matches.get_flag("extract") {
+        searcher = searcher
+            .with_extractor(PdfExtractor)
+            .with_extractor(OfficeExtractor);
+    }
+    if let Some(threads) = matches.get_one::<usize>("threads").copied().or(config.threads) {
+        searcher = searcher.with_threads(threads);
+    }
+
+    // Per-file output is for scripts: no banner, no spinner, just paths
+    if output != OutputMode::Lines {
+        match searcher
+            .with_progress(ProgressLevel::Off)
+            .search_any(&patterns)
+        {
+            Ok(report) => {
+                if output == OutputMode::Count {
+                    GrepRenderer.print_counts(&report);
+                } else {
+                    GrepRenderer.print_paths(&report);
+                }
+                print_errors(&report.errors, error_display(matches));
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+        return;
+    }
+
+    if matches.get_flag("json") {
+        match searcher.search_any(&patterns) {
+            Ok(report) => {
+                let _ = JsonRenderer.print_grep_report(&report);
+                print_errors(&report.errors, error_display(matches));
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+        return;
+    }
 
     println!("{}", "🔎 Grep Search Mode".bright_green().bold());
     println!("{}", "═".repeat(50).bright_blue());
 
-    let searcher = GrepSearcher::new(directory, case_insensitive, use_regex, extensions);
-    searcher.search(pattern);
+    run_grep_search(&searcher, &patterns, progress, error_display(matches));
+}
+
+fn handle_replace(matches: &ArgMatches, config: &Config) {
+    let pattern = matches.get_one::<String>("pattern").unwrap();
+    let replacement = matches.get_one::<String>("replacement").unwrap();
+    let directory = matches
+        .get_one::<String>("directory")
+        .map(|s| s.as_str())
+        .unwrap_or(".");
+    let flags = default_flags(matches, config);
+    let case_insensitive = flags.ignore_case;
+    let use_regex = flags.regex;
+    let extensions = matches
+        .get_one::<String>("extensions")
+        .or(config.defaults.extensions.as_ref());
+    let config_excludes = config.excludes.as_ref().map(|list| list.join(","));
+    let excludes = matches
+        .get_one::<String>("exclude")
+        .or(config_excludes.as_ref());
+    let dry_run = matches.get_flag("dry-run");
+    let backup = matches.get_flag("backup");
+    let mut apply_all = matches.get_flag("yes");
+
+    let term = Term::stdout();
+    if !dry_run && !apply_all && !term.is_term() {
+        eprintln!(
+            "{} Not a terminal, so changes can't be confirmed; pass --yes or --dry-run",
+            "❌".red()
+        );
+        std::process::exit(2);
+    }
+
+    let mut searcher = GrepSearcher::new(directory, case_insensitive, use_regex, extensions)
+        .with_excludes(excludes)
+        .with_progress(config.progress)
+        .with_walk_options(walk_options(&flags));
+    if let Some(threads) = matches.get_one::<usize>("threads").copied().or(config.threads) {
+        searcher = searcher.with_threads(threads);
+    }
+
+    let plan = match replace::plan(&searcher, pattern, replacement) {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("{} {}", "❌".red(), e);
+            std::process::exit(1);
+        }
+    };
+
+    let renderer = ReplaceRenderer;
+    renderer.print_header(&searcher, &plan, dry_run);
+    let (mut files_changed, mut hunks_applied) = (0, 0);
+    let mut unwritten = Vec::new();
+    let mut quit = false;
+    for (i, edit) in plan.edits.iter().enumerate() {
+        if quit {
+            break;
+        }
+        renderer.print_file(edit, i + 1);
+        let mut accepted = vec![false; edit.hunks.len()];
+        for (hunk, accept) in edit.hunks.iter().zip(accepted.iter_mut()) {
+            renderer.print_hunk(edit, hunk);
+            if dry_run || apply_all {
+                *accept = true;
+                continue;
+            }
+            match confirm_hunk(&term) {
+                HunkChoice::Yes => *accept = true,
+                HunkChoice::No => {}
+                HunkChoice::All => {
+                    *accept = true;
+                    apply_all = true;
+                }
+                HunkChoice::SkipFile => break,
+                HunkChoice::Quit => {
+                    quit = true;
+                    break;
+                }
+            }
+        }
+
+        let count = accepted.iter().filter(|&&ok| ok).count();
+        if count == 0 {
+            continue;
+        }
+        if !dry_run {
+            if let Err(e) = edit.write(&accepted, backup) {
+                unwritten.push(PathError {
+                    path: edit.path.clone(),
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        }
+        files_changed += 1;
+        hunks_applied += count;
+    }
+
+    renderer.print_summary(
+        files_changed,
+        hunks_applied,
+        dry_run,
+        &plan.errors,
+        &unwritten,
+    );
+    print_errors(&plan.errors, error_display(matches));
+    print_errors(&unwritten, ErrorDisplay::All);
+    if !unwritten.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+enum HunkChoice {
+    Yes,
+    No,
+    // This hunk and every one after it, in every file
+    All,
+    // None of the rest of this file
+    SkipFile,
+    Quit,
+}
+
+fn confirm_hunk(term: &Term) -> HunkChoice {
+    print!(
+        "   {} ",
+        "Apply? [y]es [n]o [a]ll remaining [s]kip file [q]uit".bright_yellow()
+    );
+    let _ = std::io::Write::flush(&mut std::io::stdout());
+    loop {
+        let choice = match term.read_char() {
+            Ok('y' | 'Y') => HunkChoice::Yes,
+            Ok('n' | 'N') => HunkChoice::No,
+            Ok('a' | 'A') => HunkChoice::All,
+            Ok('s' | 'S') => HunkChoice::SkipFile,
+            // A closed or broken terminal stops without applying anything more
+            Ok('q' | 'Q' | '\u{1b}') | Err(_) => HunkChoice::Quit,
+            Ok(_) => continue,
+        };
+        println!();
+        return choice;
+    }
+}
+
+fn handle_live_search(matches: &ArgMatches, config: &Config) {
+    let directory = matches
+        .get_one::<String>("directory")
+        .map(|s| s.as_str())
+        .unwrap_or(".");
+    let flags = default_flags(matches, config);
+    let case_insensitive = flags.ignore_case;
+    let use_regex = flags.regex;
+    let extensions = matches
+        .get_one::<String>("extensions")
+        .or(config.defaults.extensions.as_ref());
+    let excludes = config.excludes.as_ref().map(|list| list.join(","));
+    let mode = if matches.get_flag("find") {
+        LiveMode::Find
+    } else {
+        LiveMode::Grep
+    };
+
+    let mut live = LiveSearch::new(directory, case_insensitive, use_regex, extensions)
+        .with_mode(mode)
+        .with_excludes(excludes.as_ref())
+        .with_walk_options(walk_options(&flags));
+    if let Some(query) = matches.get_one::<String>("query") {
+        live = live.with_query(query);
+    }
+    if let Some(threads) = config.threads {
+        live = live.with_threads(threads);
+    }
+
+    // Print the pick so the command composes with editors and scripts
+    match live.run() {
+        Ok(Some(selection)) => match selection.line_number {
+            Some(line) => println!("{}:{}", selection.path.display(), line),
+            None => println!("{}", selection.path.display()),
+        },
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{} {}", "❌".red(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// The saved flags, with a flag or its negation from the command line winning
+fn default_flags(matches: &ArgMatches, config: &Config) -> DefaultFlags {
+    let given = |on: &str, off: &str| {
+        if matches.get_flag(on) {
+            Some(true)
+        } else if matches.get_flag(off) {
+            Some(false)
+        } else {
+            None
+        }
+    };
+    config.defaults.merge(FlagOverrides {
+        ignore_case: given("case-insensitive", "case-sensitive"),
+        regex: given("regex", "no-regex"),
+        hidden: given("hidden", "no-hidden"),
+        no_ignore: given("no-ignore", "ignore"),
+        follow: given("follow", "no-follow"),
+    })
+}
+
+fn walk_options(flags: &DefaultFlags) -> WalkOptions {
+    WalkOptions {
+        respect_ignore: !flags.no_ignore,
+        hidden: flags.hidden,
+        follow_links: flags.follow,
+        ..WalkOptions::default()
+    }
+}
+
+fn error_display(matches: &ArgMatches) -> ErrorDisplay {
+    if matches.get_flag("quiet-errors") {
+        ErrorDisplay::Quiet
+    } else if matches.get_flag("show-errors") {
+        ErrorDisplay::All
+    } else {
+        ErrorDisplay::Summary
+    }
+}
+
+fn run_file_search(searcher: &FileSearcher, pattern: &str, errors: ErrorDisplay) {
+    let renderer = FileRenderer;
+    renderer.print_header(searcher, pattern);
+    match searcher.search(pattern) {
+        Ok(report) => {
+            renderer.print_report(&report);
+            print_errors(&report.errors, errors);
+        }
+        Err(e) => println!("{} {}", "❌".red(), e),
+    }
+}
+
+fn run_grep_search(
+    searcher: &GrepSearcher,
+    patterns: &[String],
+    progress: ProgressLevel,
+    errors: ErrorDisplay,
+) {
+    let renderer = GrepRenderer;
+    renderer.print_header(searcher, patterns);
+    match searcher.search_any(patterns) {
+        Ok(report) => {
+            renderer.print_report(&report);
+            print_errors(&report.errors, errors);
+            if progress == ProgressLevel::Verbose {
+                renderer.print_metrics(&report);
+            }
+        }
+        Err(e) => println!("{} {}", "❌".red(), e),
+    }
 }
 
 fn show_interactive_menu() {
@@ -135,7 +1171,7 @@ fn show_interactive_menu() {
     println!("{}", "═".repeat(60).bright_blue());
     println!();
 
-    let options = vec![
+    let options = [
         "🔍 Search for files",
         "🔎 Search for text patterns (grep)",
         "❌ Exit",
@@ -187,7 +1223,7 @@ fn interactive_file_search() {
 
     println!();
     let searcher = FileSearcher::new(&directory, case_insensitive, use_regex);
-    searcher.search(&filename);
+    run_file_search(&searcher, &filename, ErrorDisplay::default());
 }
 
 fn interactive_grep_search() {
@@ -222,7 +1258,21 @@ fn interactive_grep_search() {
         .interact_text()
         .ok();
 
+    let threads: String = dialoguer::Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Number of threads (or press Enter for auto)")
+        .allow_empty(true)
+        .interact_text()
+        .unwrap();
+
     println!();
-    let searcher = GrepSearcher::new(&directory, case_insensitive, use_regex, extensions.as_ref());
-    searcher.search(&pattern);
+    let mut searcher = GrepSearcher::new(&directory, case_insensitive, use_regex, extensions.as_ref());
+    if let Ok(threads) = threads.trim().parse::<usize>() {
+        searcher = searcher.with_threads(threads);
+    }
+    run_grep_search(
+        &searcher,
+        &[pattern],
+        ProgressLevel::default(),
+        ErrorDisplay::default(),
+    );
 }
@@ -0,0 +1,248 @@
+use aho_corasick::{AhoCorasick, Input, MatchKind};
+use regex::bytes::{Regex, RegexBuilder, RegexSet, RegexSetBuilder};
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::error::SearchError;
+
+// Patterns for `grep -f`, one per line. Blank lines are skipped rather than
+// matching everything.
+pub fn read_patterns(path: &Path) -> io::Result<Vec<String>> {
+    let text = fs::read_to_string(path)?;
+    Ok(text
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+// A match on a line, and which pattern it was
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Found {
+    pub start: usize,
+    pub end: usize,
+    pub pattern: usize,
+}
+
+// The patterns grep reports matches for, compiled once per search
+#[derive(Clone)]
+pub(crate) enum Matcher {
+    Regex {
+        line: Regex,
+        // `line` in multi-line mode, for finding candidates in a whole file
+        buffer: Regex,
+        // With several patterns `line` is their alternation; each one on its
+        // own tells which of them a match came from
+        each: Vec<Regex>,
+    },
+    // Plain strings, found in one pass however many there are
+    Literals(AhoCorasick),
+}
+
+impl Matcher {
+    // `sources` are regexes, already escaped and flagged
+    pub(crate) fn regexes(sources: &[String]) -> Result<Self, SearchError> {
+        let joined = match sources {
+            [single] => single.clone(),
+            _ => sources
+                .iter()
+                .map(|source| format!("(?:{})", source))
+                .collect::<Vec<_>>()
+                .join("|"),
+        };
+        let each = match sources.len() {
+            1 => Vec::new(),
+            _ => sources
+                .iter()
+                .map(|source| Regex::new(source))
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(Matcher::Regex {
+            line: Regex::new(&joined)?,
+            buffer: buffer_regex(&joined)?,
+            each,
+        })
+    }
+
+    // Earlier patterns win where several match at the same place, as with
+    // a regex alternation. Case folding is ASCII only.
+    pub(crate) fn literals(
+        patterns: &[String],
+        case_insensitive: bool,
+    ) -> Result<Self, SearchError> {
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostFirst)
+            .ascii_case_insensitive(case_insensitive)
+            .build(patterns)?;
+        Ok(Matcher::Literals(automaton))
+    }
+
+    // Every match in one line, in order
+    pub(crate) fn find_line(&self, line: &[u8]) -> Vec<Found> {
+        match self {
+            Matcher::Regex {
+                line: regex, each, ..
+            } => regex
+                .find_iter(line)
+                .map(|found| Found {
+                    start: found.start(),
+                    end: found.end(),
+                    pattern: each
+                        .iter()
+                        .position(|r| {
+                            r.find_at(line, found.start())
+                                .is_some_and(|m| m.start() == found.start())
+                        })
+                        .unwrap_or(0),
+                })
+                .collect(),
+            Matcher::Literals(automaton) => automaton
+                .find_iter(line)
+                .map(|found| Found {
+                    start: found.start(),
+                    end: found.end(),
+                    pattern: found.pattern().as_usize(),
+                })
+                .collect(),
+        }
+    }
+
+    // The first match in a whole file at or after `pos`, where `^` and `$`
+    // match at line boundaries
+    pub(crate) fn find_buffer(&self, buffer: &[u8], pos: usize) -> Option<Range<usize>> {
+        match self {
+            Matcher::Regex { buffer: regex, .. } => regex.find_at(buffer, pos).map(|m| m.range()),
+            Matcher::Literals(automaton) => automaton
+                .find(Input::new(buffer).range(pos..))
+                .map(|m| m.range()),
+        }
+    }
+}
+
+// A file-level query: every `--all-of` pattern appears somewhere in the
+// file and no `--none-of` pattern does
+#[derive(Clone)]
+pub(crate) struct Query {
+    // The first `all_of` patterns of `set` are required, the rest forbidden
+    all_of: usize,
+    set: PatternSet,
+}
+
+#[derive(Clone)]
+enum PatternSet {
+    Regex(RegexSet),
+    // Overlapping, so a pattern inside another is still seen
+    Literals(AhoCorasick),
+}
+
+impl Query {
+    pub(crate) fn regexes(all_of: &[String], none_of: &[String]) -> Result<Self, SearchError> {
+        let set = RegexSetBuilder::new(all_of.iter().chain(none_of))
+            .multi_line(true)
+            .crlf(true)
+            .build()?;
+        Ok(Query {
+            all_of: all_of.len(),
+            set: PatternSet::Regex(set),
+        })
+    }
+
+    pub(crate) fn literals(
+        all_of: &[String],
+        none_of: &[String],
+        case_insensitive: bool,
+    ) -> Result<Self, SearchError> {
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .ascii_case_insensitive(case_insensitive)
+            .build(all_of.iter().chain(none_of))?;
+        Ok(Query {
+            all_of: all_of.len(),
+            set: PatternSet::Literals(automaton),
+        })
+    }
+
+    // One flag per pattern, for `mark` to fill in
+    pub(crate) fn unseen(&self) -> Vec<bool> {
+        let len = match &self.set {
+            PatternSet::Regex(set) => set.len(),
+            PatternSet::Literals(automaton) => automaton.patterns_len(),
+        };
+        vec![false; len]
+    }
+
+    // Note which patterns appear in `haystack`
+    pub(crate) fn mark(&self, haystack: &[u8], seen: &mut [bool]) {
+        match &self.set {
+            PatternSet::Regex(set) => {
+                for index in set.matches(haystack).iter() {
+                    seen[index] = true;
+                }
+            }
+            PatternSet::Literals(automaton) => {
+                for found in automaton.find_overlapping_iter(haystack) {
+                    seen[found.pattern().as_usize()] = true;
+                }
+            }
+        }
+    }
+
+    // True once a forbidden pattern has turned up; the rest of the file
+    // can't change the answer
+    pub(crate) fn failed(&self, seen: &[bool]) -> bool {
+        seen[self.all_of..].iter().any(|&s| s)
+    }
+
+    pub(crate) fn passes(&self, seen: &[bool]) -> bool {
+        seen[..self.all_of].iter().all(|&s| s) && !self.failed(seen)
+    }
+}
+
+// `^` and `$` must still mean line boundaries, including before a `\r\n`
+pub(crate) fn buffer_regex(source: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(source)
+        .multi_line(true)
+        .crlf(true)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matchers_report_pattern() {
+        let patterns = ["cat".to_string(), "category".to_string(), "dog".to_string()];
+        let found = |matcher: &Matcher| -> Vec<(usize, usize)> {
+            let line = b"category dog cat";
+            let all = matcher.find_line(line);
+            all.iter().map(|f| (f.start, f.pattern)).collect()
+        };
+
+        // Both agree that the first pattern to match at a spot wins
+        let regexes = Matcher::regexes(&patterns.clone().map(|p| regex::escape(&p))).unwrap();
+        let literals = Matcher::literals(&patterns, false).unwrap();
+        assert_eq!(found(&regexes), vec![(0, 0), (9, 2), (13, 0)]);
+        assert_eq!(found(&literals), found(&regexes));
+        assert_eq!(literals.find_buffer(b"a\ndog", 1), Some(2..5));
+    }
+
+    #[test]
+    fn test_query() {
+        let all_of = ["foo".to_string(), "bar".to_string()];
+        let none_of = ["foobar".to_string()];
+        let query = Query::literals(&all_of, &none_of, true).unwrap();
+
+        let mut seen = query.unseen();
+        query.mark(b"FOO", &mut seen);
+        assert!(!query.passes(&seen));
+        query.mark(b"bar", &mut seen);
+        assert!(query.passes(&seen));
+        // Overlapping matches still count
+        query.mark(b"xfoobarx", &mut seen);
+        assert!(query.failed(&seen));
+    }
+}
@@ -0,0 +1,714 @@
+use colored::*;
+use std::path::Path;
+
+use crate::error::PathError;
+use crate::file_search::{FileMatch, FileReport, FileSearcher, NameSyntax};
+use crate::filter::EntryType;
+use crate::grep_search::{BinaryMatch, BinaryMode, ContextLine, GrepReport, GrepSearcher, Match};
+use crate::replace::{FileEdit, Hunk, ReplacePlan};
+use crate::utils::{format_file_size, format_modified_time, get_file_icon};
+use crate::walk::SymlinkLoop;
+
+// Colorful terminal output for `find`
+pub struct FileRenderer;
+
+// How much of a report's `errors` to print after the results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorDisplay {
+    // Only the count in the summary line
+    Quiet,
+    // The first few, then how many more there are
+    #[default]
+    Summary,
+    // Every one
+    All,
+}
+
+// Errors listed before `ErrorDisplay::Summary` cuts the list short
+const SUMMARY_ERRORS: usize = 10;
+
+impl FileRenderer {
+    pub fn print_header(&self, searcher: &FileSearcher, pattern: &str) {
+        println!(
+            "{} Searching for: {}",
+            "🔍".bright_yellow(),
+            pattern.bright_white().bold()
+        );
+        println!(
+            "{} Directory: {}",
+            "📁".bright_blue(),
+            searcher.directory.display().to_string().bright_cyan()
+        );
+        println!(
+            "{} Options: {} | {}{}",
+            "⚙️".bright_magenta(),
+            if searcher.case_insensitive {
+                "Case Insensitive".green()
+            } else {
+                "Case Sensitive".red()
+            },
+            match searcher.syntax.resolve(pattern) {
+                NameSyntax::Regex => "Regex".green(),
+                NameSyntax::Glob => "Glob".cyan(),
+                NameSyntax::Fuzzy => "Fuzzy".magenta(),
+                _ => "Literal".yellow(),
+            },
+            match searcher.filters.entry_type {
+                _ if searcher.filters.broken_symlinks => " | Broken symlinks",
+                EntryType::File => "",
+                EntryType::Directory => " | Directories",
+                EntryType::Symlink => " | Symlinks",
+                EntryType::Any => " | Any type",
+            }
+            .bright_blue()
+        );
+        println!("{}", "─".repeat(80).bright_black());
+    }
+
+    pub fn print_report(&self, report: &FileReport) {
+        print_symlink_loops(&report.symlink_loops);
+        let matches = &report.files;
+        if matches.is_empty() {
+            println!(
+                "{} No files found matching: {}{}",
+                "😔".bright_red(),
+                report.pattern.bright_white().bold(),
+                unreadable_note(&report.errors)
+            );
+            return;
+        }
+
+        println!(
+            "{} Found {} match{}:",
+            "🎉".bright_green(),
+            matches.len().to_string().bright_yellow().bold(),
+            if matches.len() == 1 { "" } else { "es" }
+        );
+        println!("{}", "═".repeat(80).bright_blue());
+
+        for (index, file) in matches.iter().enumerate() {
+            self.print_file_info(file, index + 1);
+            if index < matches.len() - 1 {
+                println!("{}", "─".repeat(80).bright_black());
+            }
+        }
+
+        println!("{}", "═".repeat(80).bright_blue());
+        println!(
+            "{} Search completed. Found {} file{}{}.",
+            "✅".bright_green(),
+            matches.len().to_string().bright_yellow().bold(),
+            if matches.len() == 1 { "" } else { "s" },
+            unreadable_note(&report.errors)
+        );
+    }
+
+    fn print_file_info(&self, file: &FileMatch, index: usize) {
+        let path = &file.path;
+        let size = match file.size {
+            Some(size) => format_file_size(size),
+            // A dangling symlink still has a name and a target to show
+            None if file.kind == EntryType::Symlink => "broken link".to_string(),
+            None => {
+                println!(
+                    "{} {}. {} {}",
+                    "❌".red(),
+                    index.to_string().bright_white().bold(),
+                    get_file_icon(path),
+                    path.display().to_string().bright_red()
+                );
+                return;
+            }
+        };
+
+        let filename = path.file_name().unwrap().to_string_lossy();
+        let directory = path.parent().unwrap_or(Path::new("")).display().to_string();
+        let modified = file
+            .modified
+            .map(format_modified_time)
+            .unwrap_or_else(|| "unknown".to_string());
+
+        // Fuzzy matches show which characters matched and the score
+        let (filename, directory, score) = match &file.fuzzy {
+            Some(fuzzy) => {
+                let name_offset = path.to_string_lossy().len() - filename.len();
+                (
+                    highlight_positions(&filename, name_offset, &fuzzy.positions, |s| {
+                        s.bright_white().bold()
+                    }),
+                    highlight_positions(&directory, 0, &fuzzy.positions, |s| s.bright_cyan()),
+                    format!(" (score {})", fuzzy.score).dimmed().to_string(),
+                )
+            }
+            None => (
+                filename.bright_white().bold().to_string(),
+                directory.bright_cyan().to_string(),
+                String::new(),
+            ),
+        };
+
+        let (marker, icon) = match file.kind {
+            EntryType::Directory => ("📁", "🗂️".to_string()),
+            EntryType::Symlink => ("🔗", get_file_icon(path)),
+            _ => ("📄", get_file_icon(path)),
+        };
+        let target = match (&file.link_target, file.size) {
+            (Some(target), Some(_)) => format!(" → {}", target.display()).bright_magenta(),
+            (Some(target), None) => format!(" → {}", target.display()).bright_red(),
+            (None, _) => "".normal(),
+        };
+
+        println!(
+            "{} {}. {} {}{}{}",
+            marker.bright_blue(),
+            index.to_string().bright_white().bold(),
+            icon,
+            filename,
+            target,
+            score
+        );
+
+        println!("   {} {}", "📍".bright_yellow(), directory);
+
+        // Directories show their total size and how much they contain
+        let size = match file.entries {
+            Some(entries) => format!(
+                "{} in {} entr{}",
+                size,
+                entries,
+                if entries == 1 { "y" } else { "ies" }
+            ),
+            None => size,
+        };
+        let size_icon = if file.entries.is_some() { "📦" } else { "📏" };
+        println!(
+            "   {} {}  {} {}  {} {}",
+            size_icon.bright_magenta(),
+            size.bright_white(),
+            "🕒".bright_green(),
+            modified.bright_white(),
+            "🔗".bright_blue(),
+            path.display().to_string().dimmed()
+        );
+    }
+}
+
+// The diagnostics section after the results. Like the loop warnings it goes
+// to stderr, so piping the results never picks it up.
+pub fn print_errors(errors: &[PathError], display: ErrorDisplay) {
+    if errors.is_empty() || display == ErrorDisplay::Quiet {
+        return;
+    }
+
+    eprintln!(
+        "{} {} path{} could not be read:",
+        "⚠️".bright_yellow(),
+        errors.len().to_string().bright_red().bold(),
+        if errors.len() == 1 { "" } else { "s" }
+    );
+    let shown = match display {
+        ErrorDisplay::All => errors.len(),
+        _ => errors.len().min(SUMMARY_ERRORS),
+    };
+    for error in &errors[..shown] {
+        eprintln!(
+            "   {} {}: {}",
+            "❌".red(),
+            error.path.display().to_string().bright_red(),
+            error.message
+        );
+    }
+    if shown < errors.len() {
+        eprintln!(
+            "   ... and {} more (--show-errors lists them all)",
+            errors.len() - shown
+        );
+    }
+}
+
+// Patterns shown with their colors in the grep header
+const LEGEND_PATTERNS: usize = 8;
+
+// Matched text, in a background color picked by which pattern matched
+fn highlight(text: &str, pattern: usize) -> ColoredString {
+    let text = text.black().bold();
+    match pattern % 6 {
+        0 => text.on_bright_yellow(),
+        1 => text.on_bright_cyan(),
+        2 => text.on_bright_magenta(),
+        3 => text.on_bright_green(),
+        4 => text.on_bright_red(),
+        _ => text.on_bright_blue(),
+    }
+}
+
+// Appended to the summary line so a search that skipped paths never looks clean
+fn binary_note(binary_matches: &[BinaryMatch]) -> ColoredString {
+    match binary_matches.len() {
+        0 => "".normal(),
+        1 => " and 1 binary file".bright_yellow(),
+        n => format!(" and {} binary files", n).bright_yellow(),
+    }
+}
+
+fn unreadable_note(errors: &[PathError]) -> ColoredString {
+    match errors.len() {
+        0 => "".normal(),
+        1 => " (1 path could not be read)".bright_red(),
+        n => format!(" ({} paths could not be read)", n).bright_red(),
+    }
+}
+
+// Warnings go to stderr so they never mix with results, JSON included
+pub(crate) fn print_symlink_loops(loops: &[SymlinkLoop]) {
+    for found in loops {
+        eprintln!(
+            "{} Symlink loop: {} → {} (not followed)",
+            "⚠️".bright_yellow(),
+            found.link.display().to_string().yellow(),
+            found.ancestor.display()
+        );
+    }
+}
+
+// Color the characters of `text` whose byte offset, counted from `offset`,
+// is in `positions`, and the rest with `plain`
+fn highlight_positions(
+    text: &str,
+    offset: usize,
+    positions: &[usize],
+    plain: impl Fn(&str) -> ColoredString,
+) -> String {
+    let mut out = String::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in text.char_indices() {
+        let matched = positions.binary_search(&(offset + i)).is_ok();
+        if matched != run_matched && !run.is_empty() {
+            out.push_str(&paint_run(&run, run_matched, &plain));
+            run.clear();
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+    out.push_str(&paint_run(&run, run_matched, &plain));
+    out
+}
+
+fn paint_run(run: &str, matched: bool, plain: &impl Fn(&str) -> ColoredString) -> String {
+    if run.is_empty() {
+        String::new()
+    } else if matched {
+        run.bright_yellow().bold().underline().to_string()
+    } else {
+        plain(run).to_string()
+    }
+}
+
+// Colorful terminal output for `grep`
+pub struct GrepRenderer;
+
+impl GrepRenderer {
+    pub fn print_header(&self, searcher: &GrepSearcher, patterns: &[String]) {
+        match searcher.shown(patterns) {
+            [pattern] => println!(
+                "{} Searching for pattern: {}",
+                "🔎".bright_yellow(),
+                pattern.bright_white().bold()
+            ),
+            shown => {
+                // Each pattern in the color its matches will have
+                let legend: Vec<String> = shown
+                    .iter()
+                    .take(LEGEND_PATTERNS)
+                    .enumerate()
+                    .map(|(i, pattern)| highlight(pattern, i).to_string())
+                    .collect();
+                let more = match shown.len().saturating_sub(LEGEND_PATTERNS) {
+                    0 => String::new(),
+                    n => format!(" and {} more", n),
+                };
+                println!(
+                    "{} Searching for {} patterns: {}{}",
+                    "🔎".bright_yellow(),
+                    shown.len().to_string().bright_white().bold(),
+                    legend.join(" "),
+                    more
+                );
+            }
+        }
+        if !searcher.all_of.is_empty() {
+            println!(
+                "{} Files with all of: {}",
+                "🧩".bright_green(),
+                searcher.all_of.join(", ").bright_white()
+            );
+        }
+        if !searcher.none_of.is_empty() {
+            println!(
+                "{} Files with none of: {}",
+                "🧩".bright_red(),
+                searcher.none_of.join(", ").bright_white()
+            );
+        }
+        println!(
+            "{} Directory: {}",
+            "📁".bright_blue(),
+            searcher.directory.display().to_string().bright_cyan()
+        );
+
+        let ext_info = if let Some(ref exts) = searcher.extensions {
+            format!(
+                "Extensions: {}",
+                exts.iter().cloned().collect::<Vec<_>>().join(", ")
+            )
+            .bright_magenta()
+        } else {
+            "All files".bright_magenta()
+        };
+
+        println!(
+            "{} Options: {} | {} | {} | {}",
+            "⚙️".bright_magenta(),
+            if searcher.case_insensitive {
+                "Case Insensitive".green()
+            } else {
+                "Case Sensitive".red()
+            },
+            if searcher.use_regex {
+                "Regex".green()
+            } else {
+                "Literal".yellow()
+            },
+            ext_info,
+            format!("Threads: {}", searcher.threads).bright_blue()
+        );
+        if !searcher.excludes.is_empty() {
+            println!(
+                "{} Excluding: {}",
+                "🚫".bright_red(),
+                searcher.excludes.join(", ").bright_black()
+            );
+        }
+        if let Some(encoding) = searcher.encoding {
+            println!(
+                "{} Encoding: {} (unless a BOM says otherwise)",
+                "🔤".bright_cyan(),
+                encoding.name().bright_white()
+            );
+        }
+        if searcher.search_zip {
+            println!(
+                "{} Compressed files: {}",
+                "🗜️".bright_magenta(),
+                "gz, bz2, xz and zst are decompressed and searched".bright_white()
+            );
+        }
+        if searcher.invert {
+            println!(
+                "{} Inverted: {}",
+                "🔁".bright_yellow(),
+                "showing lines that don't match".bright_white()
+            );
+        }
+        match searcher.binary {
+            BinaryMode::Skip => {}
+            BinaryMode::Search => println!(
+                "{} Binary files: {}",
+                "📦".bright_yellow(),
+                "reporting match offsets".bright_white()
+            ),
+            BinaryMode::Text => println!(
+                "{} Binary files: {}",
+                "📦".bright_yellow(),
+                "searched as text".bright_white()
+            ),
+        }
+        println!("{}", "─".repeat(80).bright_black());
+    }
+
+    pub fn print_report(&self, report: &GrepReport) {
+        print_symlink_loops(&report.symlink_loops);
+        let matches = &report.matches;
+        if matches.is_empty() && report.binary_matches.is_empty() {
+            println!(
+                "{} No matches found for pattern: {} (searched {} files){}",
+                "😔".bright_red(),
+                report.pattern.bright_white().bold(),
+                report.files_searched.to_string().bright_yellow(),
+                unreadable_note(&report.errors)
+            );
+            return;
+        }
+
+        let file_matches = report.files();
+        let file_context = report.context_by_file();
+        let show_dividers = !report.context.is_empty();
+
+        println!(
+            "{} Found {} match{} in {} file{}{} (searched {} files):",
+            "🎉".bright_green(),
+            matches.len().to_string().bright_yellow().bold(),
+            if matches.len() == 1 { "" } else { "es" },
+            file_matches.len().to_string().bright_cyan().bold(),
+            if file_matches.len() == 1 { "" } else { "s" },
+            binary_note(&report.binary_matches),
+            report.files_searched.to_string().bright_white()
+        );
+        println!("{}", "═".repeat(80).bright_blue());
+
+        let mut file_index = 1;
+        for (file_path, file_match_list) in &file_matches {
+            let context = file_context
+                .get(file_path)
+                .map(Vec::as_slice)
+                .unwrap_or(&[]);
+            self.print_file_matches(
+                file_path,
+                file_match_list,
+                context,
+                file_index,
+                show_dividers,
+            );
+            file_index += 1;
+
+            if file_index <= file_matches.len() {
+                println!("{}", "─".repeat(80).bright_black());
+            }
+        }
+        if !report.binary_matches.is_empty() {
+            if !file_matches.is_empty() {
+                println!("{}", "─".repeat(80).bright_black());
+            }
+            for binary in &report.binary_matches {
+                self.print_binary_match(binary);
+            }
+        }
+
+        println!("{}", "═".repeat(80).bright_blue());
+        println!(
+            "{} Search completed. Found {} match{} in {} file{}{}{}.",
+            "✅".bright_green(),
+            matches.len().to_string().bright_yellow().bold(),
+            if matches.len() == 1 { "" } else { "es" },
+            file_matches.len().to_string().bright_cyan().bold(),
+            if file_matches.len() == 1 { "" } else { "s" },
+            binary_note(&report.binary_matches),
+            unreadable_note(&report.errors)
+        );
+    }
+
+    // `path:count` per file with -c, plain so scripts can cut it apart
+    pub fn print_counts(&self, report: &GrepReport) {
+        for file in &report.file_counts {
+            println!(
+                "{}:{}",
+                file.file_path.display().to_string().bright_cyan(),
+                file.count
+            );
+        }
+    }
+
+    // One path per line with -l and -L
+    pub fn print_paths(&self, report: &GrepReport) {
+        for file in &report.file_counts {
+            println!("{}", file.file_path.display().to_string().bright_cyan());
+        }
+    }
+
+    pub fn print_metrics(&self, report: &GrepReport) {
+        let secs = report.elapsed.as_secs_f64().max(f64::EPSILON);
+        println!(
+            "{} {:.3}s | {} files/s | {}/s",
+            "⏱️".bright_cyan(),
+            secs,
+            format!("{:.0}", report.files_searched as f64 / secs).bright_white(),
+            format_file_size((report.bytes_searched as f64 / secs) as u64).bright_white()
+        );
+    }
+
+    fn print_binary_match(&self, binary: &BinaryMatch) {
+        // A handful of offsets is enough to find the spot in a hex viewer
+        let shown: Vec<String> = binary.offsets.iter().take(5).map(u64::to_string).collect();
+        let more = if binary.count > shown.len() {
+            ", ..."
+        } else {
+            ""
+        };
+        println!(
+            "{} {}: binary file matches ({} match{} at byte offset{} {}{})",
+            "📦".bright_yellow(),
+            binary.file_path.display().to_string().bright_cyan().bold(),
+            binary.count.to_string().bright_yellow(),
+            if binary.count == 1 { "" } else { "es" },
+            if binary.count == 1 { "" } else { "s" },
+            shown.join(", ").bright_white(),
+            more
+        );
+    }
+
+    fn print_file_matches(
+        &self,
+        file_path: &Path,
+        matches: &[&Match],
+        context: &[&ContextLine],
+        file_index: usize,
+        show_dividers: bool,
+    ) {
+        let filename = file_path.file_name().unwrap().to_string_lossy();
+        let directory = file_path.parent().unwrap_or(Path::new("")).display();
+
+        println!(
+            "{} {}. {} {} ({} match{})",
+            "📄".bright_blue(),
+            file_index.to_string().bright_white().bold(),
+            get_file_icon(file_path),
+            filename.bright_white().bold(),
+            matches.len().to_string().bright_yellow(),
+            if matches.len() == 1 { "" } else { "es" }
+        );
+
+        println!(
+            "   {} {}",
+            "📍".bright_yellow(),
+            directory.to_string().bright_cyan()
+        );
+
+        // Interleave matches and context lines, with a divider between
+        // groups that are not adjacent, like GNU grep
+        let mut context = context.iter().peekable();
+        let mut last_line: Option<usize> = None;
+        let mut print_divider = |line_number: usize| {
+            if show_dividers && last_line.is_some_and(|last| line_number > last + 1) {
+                println!("     {}", "--".bright_black());
+            }
+            last_line = Some(line_number);
+        };
+
+        for (i, m) in matches.iter().enumerate() {
+            while let Some(line) = context.next_if(|c| c.line_number < m.line_number) {
+                print_divider(line.line_number);
+                self.print_context_line(line);
+            }
+            print_divider(m.line_number);
+            self.print_match(m, i + 1);
+        }
+        for line in context {
+            print_divider(line.line_number);
+            self.print_context_line(line);
+        }
+    }
+
+    fn print_context_line(&self, line: &ContextLine) {
+        println!(
+            "       {} ┆ {}",
+            format!("{:4}", line.line_number).bright_black(),
+            line.line_content.trim_start().dimmed()
+        );
+    }
+
+    fn print_match(&self, m: &Match, match_index: usize) {
+        let line_num_str = format!("{:4}", m.line_number);
+
+        // Highlight the matched text
+        let before_match = &m.line_content[..m.match_start];
+        let matched_text = &m.line_content[m.match_start..m.match_end];
+        let after_match = &m.line_content[m.match_end..];
+
+        // Text extracted from a document says where it came from
+        let location = match &m.location {
+            Some(location) => format!("[{}] ", location).bright_cyan().to_string(),
+            None => String::new(),
+        };
+
+        println!(
+            "     {} {} │ {}{}{}{}",
+            match_index.to_string().bright_magenta(),
+            line_num_str.bright_blue(),
+            location,
+            before_match.trim_start(),
+            highlight(matched_text, m.pattern),
+            after_match
+        );
+    }
+}
+
+// Colorful terminal output for `replace`: a diff per hunk
+pub struct ReplaceRenderer;
+
+impl ReplaceRenderer {
+    pub fn print_header(&self, searcher: &GrepSearcher, plan: &ReplacePlan, dry_run: bool) {
+        println!(
+            "{} Replacing: {} {} {}",
+            "✏️".bright_yellow(),
+            plan.pattern.bright_white().bold(),
+            "→".bright_black(),
+            plan.replacement.bright_green().bold()
+        );
+        println!(
+            "{} Directory: {}",
+            "📁".bright_blue(),
+            searcher.directory.display().to_string().bright_cyan()
+        );
+        if dry_run {
+            println!(
+                "{} Dry run: {}",
+                "👀".bright_magenta(),
+                "nothing will be written".bright_white()
+            );
+        }
+        println!("{}", "─".repeat(80).bright_black());
+    }
+
+    pub fn print_file(&self, edit: &FileEdit, file_index: usize) {
+        println!(
+            "{} {}. {} {} ({} change{})",
+            "📄".bright_blue(),
+            file_index.to_string().bright_white().bold(),
+            get_file_icon(&edit.path),
+            edit.path.display().to_string().bright_white().bold(),
+            edit.hunks.len().to_string().bright_yellow(),
+            if edit.hunks.len() == 1 { "" } else { "s" }
+        );
+    }
+
+    pub fn print_hunk(&self, edit: &FileEdit, hunk: &Hunk) {
+        println!(
+            "   {}",
+            format!("@@ line {} @@", hunk.line_number).bright_cyan()
+        );
+        for line in String::from_utf8_lossy(edit.old_text(hunk)).lines() {
+            println!("   {} {}", "-".red().bold(), line.red());
+        }
+        for line in String::from_utf8_lossy(&hunk.replacement).lines() {
+            println!("   {} {}", "+".green().bold(), line.green());
+        }
+    }
+
+    pub fn print_summary(
+        &self,
+        files: usize,
+        hunks: usize,
+        dry_run: bool,
+        errors: &[PathError],
+        unwritten: &[PathError],
+    ) {
+        let unwritten_note = match unwritten.len() {
+            0 => "".normal(),
+            1 => " (1 file could not be written)".bright_red(),
+            n => format!(" ({} files could not be written)", n).bright_red(),
+        };
+        println!("{}", "═".repeat(80).bright_blue());
+        println!(
+            "{} {} {} change{} in {} file{}{}{}.",
+            "✅".bright_green(),
+            if dry_run { "Would make" } else { "Made" },
+            hunks.to_string().bright_yellow().bold(),
+            if hunks == 1 { "" } else { "s" },
+            files.to_string().bright_cyan().bold(),
+            if files == 1 { "" } else { "s" },
+            unreadable_note(errors),
+            unwritten_note
+        );
+    }
+}
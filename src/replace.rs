@@ -0,0 +1,327 @@
+use regex::bytes::Regex;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{PathError, SearchError};
+use crate::grep_search::{trim_line_end, GrepSearcher};
+
+// Everything `replace` would change, before anything is written
+#[derive(Debug)]
+pub struct ReplacePlan {
+    pub pattern: String,
+    pub replacement: String,
+    // Files with at least one change, in path order
+    pub edits: Vec<FileEdit>,
+    pub errors: Vec<PathError>,
+}
+
+// The changes to one file, grouped into hunks
+#[derive(Debug, Clone)]
+pub struct FileEdit {
+    pub path: PathBuf,
+    pub original: Vec<u8>,
+    pub hunks: Vec<Hunk>,
+}
+
+// A run of adjacent changed lines, accepted or rejected as a unit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    // Line number of the first line, counting from 1
+    pub line_number: usize,
+    // Byte range of the whole lines it replaces in the original
+    pub start: usize,
+    pub end: usize,
+    // What those lines become
+    pub replacement: Vec<u8>,
+}
+
+// Find the files grep would match and work out their replacements. The
+// searcher's settings decide which files are read and how the pattern is
+// built. With a regex, `$1`, `${name}` and `$0` in `replacement` expand to
+// capture groups and `$$` is a literal `$`; a literal pattern's replacement
+// is used as is.
+pub fn plan(
+    searcher: &GrepSearcher,
+    pattern: &str,
+    replacement: &str,
+) -> Result<ReplacePlan, SearchError> {
+    let regex = searcher.line_regex(pattern)?;
+    let expansion = if searcher.use_regex {
+        replacement.to_string()
+    } else {
+        replacement.replace('$', "$$")
+    };
+    let report = searcher.search(pattern)?;
+
+    let mut edits = Vec::new();
+    let mut errors = report.errors.clone();
+    for path in report.files().into_keys() {
+        // Lines found by decoding or extraction have no bytes to rewrite
+        if !path.is_file() {
+            continue;
+        }
+        match FileEdit::plan(path, &regex, &expansion) {
+            Ok(Some(edit)) => edits.push(edit),
+            Ok(None) => {}
+            Err(e) => errors.push(PathError {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(ReplacePlan {
+        pattern: pattern.to_string(),
+        replacement: replacement.to_string(),
+        edits,
+        errors,
+    })
+}
+
+impl FileEdit {
+    // None if no match changes anything. `regex` is matched against each
+    // line without its line ending, as grep does, so no match spans lines.
+    pub fn plan(path: &Path, regex: &Regex, replacement: &str) -> io::Result<Option<Self>> {
+        let original = fs::read(path)?;
+        let mut changes = Vec::new();
+        let mut offset = 0;
+        for line in original.split_inclusive(|&b| b == b'\n') {
+            for captures in regex.captures_iter(trim_line_end(line)) {
+                let found = captures.get(0).unwrap();
+                let mut text = Vec::new();
+                captures.expand(replacement.as_bytes(), &mut text);
+                if text != found.as_bytes() {
+                    changes.push((offset + found.start(), offset + found.end(), text));
+                }
+            }
+            offset += line.len();
+        }
+
+        let mut hunks: Vec<Hunk> = Vec::new();
+        // Replacements inside the hunk being built: range and new bytes
+        let mut pending: Vec<(usize, usize, Vec<u8>)> = Vec::new();
+        let (mut counted, mut line_number) = (0, 1);
+        for (found_start, found_end, text) in changes {
+            let start = line_start(&original, found_start);
+            let end = line_end(&original, found_end);
+            match hunks.last_mut() {
+                // Touching the previous hunk's lines extends it
+                Some(hunk) if start <= hunk.end => hunk.end = end,
+                _ => {
+                    if let Some(hunk) = hunks.last_mut() {
+                        hunk.replacement = splice(&original, hunk, &pending);
+                        pending.clear();
+                    }
+                    line_number += count_lines(&original[counted..start]);
+                    counted = start;
+                    hunks.push(Hunk {
+                        line_number,
+                        start,
+                        end,
+                        replacement: Vec::new(),
+                    });
+                }
+            }
+            pending.push((found_start, found_end, text));
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            return Ok(None);
+        };
+        hunk.replacement = splice(&original, hunk, &pending);
+
+        Ok(Some(FileEdit {
+            path: path.to_path_buf(),
+            original,
+            hunks,
+        }))
+    }
+
+    pub fn old_text(&self, hunk: &Hunk) -> &[u8] {
+        &self.original[hunk.start..hunk.end]
+    }
+
+    // The file with only the accepted hunks applied
+    pub fn apply(&self, accepted: &[bool]) -> Vec<u8> {
+        let mut contents = Vec::with_capacity(self.original.len());
+        let mut copied = 0;
+        for (hunk, _) in self.hunks.iter().zip(accepted).filter(|(_, &ok)| ok) {
+            contents.extend_from_slice(&self.original[copied..hunk.start]);
+            contents.extend_from_slice(&hunk.replacement);
+            copied = hunk.end;
+        }
+        contents.extend_from_slice(&self.original[copied..]);
+        contents
+    }
+
+    // Write the accepted hunks back. Fails without writing if the file
+    // changed since it was planned.
+    pub fn write(&self, accepted: &[bool], backup: bool) -> io::Result<()> {
+        if fs::read(&self.path)? != self.original {
+            return Err(io::Error::other(
+                "changed since it was searched, not written",
+            ));
+        }
+        write_atomic(&self.path, &self.apply(accepted), backup)
+    }
+}
+
+// Replace a file's contents through a temporary file in the same directory
+// and a rename, so readers see the old file or the new one and never half
+// of each. With `backup`, the original is kept at `backup_path(path)`.
+// Symlinks are written through to their target.
+pub fn write_atomic(path: &Path, contents: &[u8], backup: bool) -> io::Result<()> {
+    let path = fs::canonicalize(path)?;
+    let name = path.file_name().unwrap_or_default();
+    let mut temp_name = OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let mut file = File::create_new(&temp)?;
+        file.write_all(contents)?;
+        file.set_permissions(fs::metadata(&path)?.permissions())?;
+        file.sync_all()?;
+        if backup {
+            fs::copy(&path, backup_path(&path))?;
+        }
+        fs::rename(&temp, &path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+// `main.rs` is backed up as `main.rs.bak`
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = OsString::from(path.as_os_str());
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+fn line_start(buffer: &[u8], offset: usize) -> usize {
+    buffer[..offset]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1)
+}
+
+// The end of the line `end` is on, including its newline
+fn line_end(buffer: &[u8], end: usize) -> usize {
+    buffer[end..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(buffer.len(), |i| end + i + 1)
+}
+
+fn count_lines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b == b'\n').count()
+}
+
+// A hunk's lines with its replacements substituted in
+fn splice(original: &[u8], hunk: &Hunk, replacements: &[(usize, usize, Vec<u8>)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut copied = hunk.start;
+    for (start, end, text) in replacements {
+        out.extend_from_slice(&original[copied..*start]);
+        out.extend_from_slice(text);
+        copied = *end;
+    }
+    out.extend_from_slice(&original[copied..hunk.end]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_and_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.rs");
+        let text = "let a = old_name(1);\nkeep\nold_name(2); old_name(3);\nold_name(4)\nkeep\n\nold_name(5)";
+        fs::write(&path, text).unwrap();
+
+        let regex = Regex::new(r"old_name\((\d)\)").unwrap();
+        let edit = FileEdit::plan(&path, &regex, "new_name($1, ${1}0)")
+            .unwrap()
+            .unwrap();
+        let lines: Vec<_> = edit.hunks.iter().map(|h| h.line_number).collect();
+        assert_eq!(lines, vec![1, 3, 7]);
+        assert_eq!(
+            edit.old_text(&edit.hunks[1]),
+            b"old_name(2); old_name(3);\nold_name(4)\n"
+        );
+        assert_eq!(
+            edit.hunks[1].replacement,
+            b"new_name(2, 20); new_name(3, 30);\nnew_name(4, 40)\n"
+        );
+
+        let applied = edit.apply(&[true, false, true]);
+        assert_eq!(
+            String::from_utf8(applied).unwrap(),
+            "let a = new_name(1, 10);\nkeep\nold_name(2); old_name(3);\nold_name(4)\nkeep\n\nnew_name(5, 50)"
+        );
+
+        // Replacing a match with itself isn't a change
+        assert!(FileEdit::plan(&path, &regex, "$0").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_plan_uses_searcher() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "Price: 5\n").unwrap();
+        fs::write(dir.path().join("b.md"), "price: 6\n").unwrap();
+
+        // A literal pattern's replacement has no capture groups to expand
+        let exts = "txt".to_string();
+        let searcher = GrepSearcher::new(dir.path().to_str().unwrap(), true, false, Some(&exts));
+        let plan = plan(&searcher, "price", "$cost").unwrap();
+        assert_eq!(plan.edits.len(), 1);
+        assert_eq!(plan.edits[0].hunks[0].replacement, b"$cost: 5\n");
+
+        // -i holds for regexes, in the search and in the rewrite
+        let searcher = GrepSearcher::new(dir.path().to_str().unwrap(), true, true, None);
+        let folded = super::plan(&searcher, r"(p)rice", "${1}ay").unwrap();
+        assert_eq!(folded.edits.len(), 2);
+        assert_eq!(folded.edits[0].hunks[0].replacement, b"Pay: 5\n");
+    }
+
+    #[test]
+    fn test_matches_stay_within_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(&path, "a b\na\nb\r\na  b\r\n").unwrap();
+
+        // `\s` doesn't reach past a line ending, just as in grep
+        let searcher = GrepSearcher::new(dir.path().to_str().unwrap(), false, true, None);
+        let plan = plan(&searcher, r"a\s+b", "X").unwrap();
+        let hunks = &plan.edits[0].hunks;
+        let lines: Vec<_> = hunks.iter().map(|h| h.line_number).collect();
+        assert_eq!(lines, vec![1, 4]);
+        assert_eq!(hunks[0].replacement, b"X\n");
+        assert_eq!(hunks[1].replacement, b"X\r\n");
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "before\n").unwrap();
+
+        let regex = Regex::new("before").unwrap();
+        let edit = FileEdit::plan(&path, &regex, "after").unwrap().unwrap();
+        edit.write(&[true], true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "after\n");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "before\n");
+        // Only the file and its backup are left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+
+        // A file edited since planning is left alone
+        assert!(edit.write(&[true], false).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "after\n");
+    }
+}
@@ -1,9 +1,16 @@
 use chrono::{DateTime, Local};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use indicatif::{ProgressBar, ProgressStyle};
 use std::collections::HashSet;
+use std::ffi::OsStr;
 use std::fs;
+use std::io::{self, Read, Seek};
 use std::path::Path;
 use std::time::SystemTime;
 
+use crate::config::ProgressLevel;
+use crate::decode::has_utf16_bom;
+
 pub fn format_file_size(size: u64) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
     const THRESHOLD: u64 = 1024;
@@ -27,6 +34,21 @@ pub fn format_file_size(size: u64) -> String {
     }
 }
 
+pub(crate) fn new_spinner(progress: ProgressLevel, message: &'static str) -> ProgressBar {
+    if progress == ProgressLevel::Off {
+        return ProgressBar::hidden();
+    }
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.green} {msg}")
+            .unwrap()
+            .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ "),
+    );
+    pb.set_message(message);
+    pb
+}
+
 pub fn format_modified_time(system_time: SystemTime) -> String {
     let datetime: DateTime<Local> = system_time.into();
     datetime.format("%Y-%m-%d %H:%M:%S").to_string()
@@ -92,7 +114,7 @@ pub fn get_file_icon(path: &Path) -> String {
         "ico" => "🎯".to_string(),
         
         // Archives
-        "zip" | "rar" | "7z" | "tar" | "gz" | "bz2" | "xz" => "📦".to_string(),
+        "zip" | "rar" | "7z" | "tar" | "gz" | "bz2" | "xz" | "zst" => "📦".to_string(),
         
         // Executables
         "exe" | "app" | "deb" | "rpm" | "dmg" | "msi" => "⚡".to_string(),
@@ -116,32 +138,76 @@ pub fn get_file_icon(path: &Path) -> String {
     }
 }
 
+// How much of a file is read to decide whether it's binary
+pub const BINARY_SAMPLE_SIZE: usize = 8 * 1024;
+
+// Magic numbers of common binary formats, checked before any heuristics
+const BINARY_MAGIC: &[&[u8]] = &[
+    // Executables: ELF, Mach-O (64-bit, 32-bit, universal) and Java classes
+    b"\x7fELF",
+    b"\xCF\xFA\xED\xFE",
+    b"\xCE\xFA\xED\xFE",
+    b"\xCA\xFE\xBA\xBE",
+    // Images and media
+    b"\x89PNG\r\n\x1A\n",
+    b"\xFF\xD8\xFF",
+    b"GIF87a",
+    b"GIF89a",
+    b"OggS",
+    b"fLaC",
+    // Documents and archives; ZIP also covers docx, xlsx, jar and apk
+    b"%PDF-",
+    b"PK\x03\x04",
+    b"\x1F\x8B",
+    b"\xFD7zXZ\0",
+    b"\x28\xB5\x2F\xFD",
+    b"7z\xBC\xAF\x27\x1C",
+    b"Rar!\x1A\x07",
+    b"SQLite format 3\0",
+];
+
+// Read the first `BINARY_SAMPLE_SIZE` bytes, leaving the file at the start
+pub(crate) fn read_sample(file: &mut fs::File) -> io::Result<Vec<u8>> {
+    let mut sample = Vec::with_capacity(BINARY_SAMPLE_SIZE);
+    file.by_ref()
+        .take(BINARY_SAMPLE_SIZE as u64)
+        .read_to_end(&mut sample)?;
+    file.rewind()?;
+    Ok(sample)
+}
+
 pub fn is_binary_file(path: &Path) -> bool {
-    // Common binary file extensions
-    let binary_extensions = [
-        "exe", "dll", "so", "dylib", "bin", "o", "obj",
-        "jpg", "jpeg", "png", "gif", "bmp", "ico", "tiff", "webp", "svg",
-        "mp3", "mp4", "avi", "mkv", "mov", "wmv", "flv", "wav", "flac",
-        "zip", "rar", "7z", "tar", "gz", "bz2", "xz",
-        "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx",
-        "ttf", "otf", "woff", "woff2",
-    ];
-
-    if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-        return binary_extensions.contains(&ext.to_lowercase().as_str());
-    }
+    fs::File::open(path)
+        .and_then(|mut file| read_sample(&mut file))
+        .is_ok_and(|sample| looks_binary(&sample))
+}
 
-    // Try to read first few bytes to detect binary
-    if let Ok(bytes) = fs::read(path) {
-        if bytes.len() > 0 {
-            let sample_size = std::cmp::min(bytes.len(), 512);
-            let null_count = bytes[..sample_size].iter().filter(|&&b| b == 0).count();
-            // If more than 1% of the sample contains null bytes, consider it binary
-            return null_count as f64 / sample_size as f64 > 0.01;
+// Classify a file by its first bytes: a known binary format, a NUL byte, or
+// text that isn't UTF-8 and is mostly control characters. Legacy encodings
+// like Latin-1 or Shift JIS aren't UTF-8 either, but they're printable.
+pub fn looks_binary(sample: &[u8]) -> bool {
+    if has_utf16_bom(sample) {
+        return false;
+    }
+    if has_binary_magic(sample) || sample.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => false,
+        // Only cut off mid-character by the sample size
+        Err(e) if e.error_len().is_none() => false,
+        Err(_) => {
+            let control = sample
+                .iter()
+                .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B))
+                .count();
+            control * 10 > sample.len()
         }
     }
+}
 
-    false
+pub fn has_binary_magic(sample: &[u8]) -> bool {
+    BINARY_MAGIC.iter().any(|magic| sample.starts_with(magic))
 }
 
 pub fn should_search_file(path: &Path, extensions: &Option<HashSet<String>>) -> bool {
@@ -154,6 +220,44 @@ pub fn should_search_file(path: &Path, extensions: &Option<HashSet<String>>) ->
     true
 }
 
+pub fn parse_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// Excludes are plain directory/file names (node_modules) or globs (*.log)
+pub struct ExcludeFilter {
+    names: HashSet<String>,
+    globs: GlobSet,
+}
+
+impl ExcludeFilter {
+    pub fn new(patterns: &[String]) -> Result<Self, globset::Error> {
+        let mut names = HashSet::new();
+        let mut builder = GlobSetBuilder::new();
+
+        for pattern in patterns {
+            if pattern.contains(['*', '?', '[', '{']) {
+                builder.add(Glob::new(pattern)?);
+            } else {
+                names.insert(pattern.clone());
+            }
+        }
+
+        Ok(Self {
+            names,
+            globs: builder.build()?,
+        })
+    }
+
+    pub fn is_excluded(&self, name: &OsStr) -> bool {
+        let name = name.to_string_lossy();
+        self.names.contains(name.as_ref()) || self.globs.is_match(name.as_ref())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,4 +278,30 @@ mod tests {
         assert_eq!(get_file_icon(Path::new("test.js")), "🟨");
         assert_eq!(get_file_icon(Path::new("test.unknown")), "📄");
     }
+
+    #[test]
+    fn test_exclude_filter() {
+        let filter = ExcludeFilter::new(&parse_list("node_modules, *.log,,target")).unwrap();
+        assert!(filter.is_excluded(OsStr::new("node_modules")));
+        assert!(filter.is_excluded(OsStr::new("target")));
+        assert!(filter.is_excluded(OsStr::new("app.log")));
+        assert!(!filter.is_excluded(OsStr::new("src")));
+        assert!(!filter.is_excluded(OsStr::new("catalog.rs")));
+    }
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(!looks_binary(b""));
+        assert!(!looks_binary(b"fn main() {}\n"));
+        assert!(!looks_binary(b"<svg width=\"10\"/>"));
+        // Latin-1 and a multi-byte character cut off by the sample size
+        assert!(!looks_binary(b"caf\xe9 ol\xe9\n"));
+        assert!(!looks_binary(&"\u{65e5}\u{672c}".as_bytes()[..4]));
+        assert!(!looks_binary(b"\xFF\xFEh\0i\0"));
+
+        assert!(looks_binary(b"\x7fELF\x02\x01\x01"));
+        assert!(looks_binary(b"%PDF-1.7\n"));
+        assert!(looks_binary(b"text\0with a nul"));
+        assert!(looks_binary(b"\x01\x02\x03\xff\x04\x05\x06\x07"));
+    }
 }
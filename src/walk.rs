@@ -0,0 +1,179 @@
+use ignore::{Walk, WalkBuilder};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use crate::error::PathError;
+use crate::utils::ExcludeFilter;
+
+// Project-level ignore file, same syntax as .gitignore
+pub const IGNORE_FILENAME: &str = ".ffignore";
+
+#[derive(Debug, Clone, Copy)]
+pub struct WalkOptions {
+    // Honor .gitignore, .git/info/exclude, global git excludes, .ignore and .ffignore
+    pub respect_ignore: bool,
+    // Descend into hidden files and directories
+    pub hidden: bool,
+    // Don't descend below this depth; the root's children are depth 1
+    pub max_depth: Option<usize>,
+    // Descend into symlinked directories and search symlinked files
+    pub follow_links: bool,
+}
+
+// A symlink that points back at one of its own ancestors, found while
+// following links. The walk reports it and doesn't descend into it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymlinkLoop {
+    pub link: PathBuf,
+    pub ancestor: PathBuf,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            respect_ignore: true,
+            hidden: false,
+            max_depth: None,
+            follow_links: false,
+        }
+    }
+}
+
+pub(crate) fn build_walker(
+    root: &Path,
+    options: WalkOptions,
+    exclude_filter: Option<ExcludeFilter>,
+) -> Walk {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .standard_filters(options.respect_ignore)
+        .hidden(!options.hidden)
+        .max_depth(options.max_depth)
+        .follow_links(options.follow_links);
+    if options.respect_ignore {
+        builder.add_custom_ignore_filename(IGNORE_FILENAME);
+    }
+    if let Some(filter) = exclude_filter {
+        builder.filter_entry(move |e| e.depth() == 0 || !filter.is_excluded(e.file_name()));
+    }
+    builder.build()
+}
+
+// The loop behind a walk error, if that's what it is
+pub(crate) fn symlink_loop(err: &ignore::Error) -> Option<SymlinkLoop> {
+    match err {
+        ignore::Error::Loop { ancestor, child } => Some(SymlinkLoop {
+            link: child.clone(),
+            ancestor: ancestor.clone(),
+        }),
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => symlink_loop(err),
+        _ => None,
+    }
+}
+
+// When following links, a dangling symlink comes back as an I/O error on its
+// path rather than as an entry
+pub(crate) fn dangling_link(err: &ignore::Error) -> Option<PathBuf> {
+    match err {
+        ignore::Error::WithPath { path, .. } if is_dangling(path) => Some(path.clone()),
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => dangling_link(err),
+        _ => None,
+    }
+}
+
+// Where a walk error happened and what went wrong, for the diagnostics list
+pub(crate) fn walk_error(err: &ignore::Error, root: &Path) -> PathError {
+    fn describe(err: &ignore::Error) -> (Option<&Path>, String) {
+        match err {
+            ignore::Error::WithPath { path, err } => {
+                let (inner, message) = describe(err);
+                (inner.or(Some(path)), message)
+            }
+            ignore::Error::WithDepth { err, .. } => describe(err),
+            ignore::Error::WithLineNumber { line, err } => {
+                let (path, message) = describe(err);
+                (path, format!("line {}: {}", line, message))
+            }
+            ignore::Error::Io(e) => (None, io_message(e)),
+            _ => (None, err.to_string()),
+        }
+    }
+
+    let (path, message) = describe(err);
+    PathError {
+        path: path.unwrap_or(root).to_path_buf(),
+        message,
+    }
+}
+
+// The walker wraps OS errors in messages that repeat the path
+fn io_message(err: &io::Error) -> String {
+    match err.get_ref().and_then(|inner| inner.source()) {
+        Some(source) => source.to_string(),
+        None => err.to_string(),
+    }
+}
+
+// A symlink whose target doesn't exist
+pub(crate) fn is_dangling(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink())
+        && fs::metadata(path).is_err()
+}
+
+// Total size of the files under each of `dirs` and how many entries each
+// holds at any depth. Like `du`, this counts ignored and hidden entries and
+// doesn't follow symlinks. Nested directories are totalled from their
+// ancestor's walk, so every entry is read once.
+pub(crate) fn directory_usage(dirs: &[PathBuf]) -> HashMap<PathBuf, (u64, u64)> {
+    let wanted: HashSet<&Path> = dirs.iter().map(PathBuf::as_path).collect();
+    // A directory reached through a followed link isn't in its ancestor's walk
+    let covered = |dir: &Path| {
+        let Some(ancestor) = dir.ancestors().skip(1).find(|a| wanted.contains(a)) else {
+            return false;
+        };
+        !dir.ancestors()
+            .take_while(|a| *a != ancestor)
+            .any(|a| a.is_symlink())
+    };
+    let roots: HashSet<&Path> = dirs
+        .iter()
+        .map(PathBuf::as_path)
+        .filter(|dir| !covered(dir))
+        .collect();
+
+    // Totals of each directory's direct children first
+    let mut usage: HashMap<PathBuf, (u64, u64)> = HashMap::new();
+    for &dir in &roots {
+        let walk = WalkBuilder::new(dir).standard_filters(false).build();
+        for entry in walk.filter_map(|e| e.ok()).filter(|e| e.depth() > 0) {
+            let size = match entry.file_type() {
+                Some(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+                _ => 0,
+            };
+            let Some(parent) = entry.path().parent() else {
+                continue;
+            };
+            let totals = usage.entry(parent.to_path_buf()).or_default();
+            totals.0 += size;
+            totals.1 += 1;
+        }
+    }
+
+    // Then add each directory into its parent, deepest first
+    let mut walked: Vec<PathBuf> = usage.keys().cloned().collect();
+    walked.retain(|dir| !roots.contains(dir.as_path()));
+    walked.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+    for dir in walked {
+        let (size, entries) = usage[&dir];
+        if let Some(totals) = dir.parent().and_then(|p| usage.get_mut(p)) {
+            totals.0 += size;
+            totals.1 += entries;
+        }
+    }
+    usage
+}